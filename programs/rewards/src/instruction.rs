@@ -1,5 +1,7 @@
 //! Instruction types
 
+use crate::find_vault_program_address;
+use crate::state::{AdminAction, DistributionMode, PenaltyDestination, Role};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::pubkey::Pubkey;
@@ -15,7 +17,8 @@ pub enum RewardsInstruction {
         lock_time_sec: u64,
     },
 
-    /// Creates a new vault account and adds it to the reward pool
+    /// Creates a new fixed ratio vault account without end time and adds it to the reward pool.
+    /// Keeps the original encoding, see AddVaultExtended
     AddVault {
         /// Reward ratio of deposit currency
         ratio_base: u64,
@@ -29,29 +32,268 @@ pub enum RewardsInstruction {
         reward_max_amount_per_period: u64,
     },
 
-    /// Fills the reward pool with rewards
+    /// Fills the reward pool with rewards.
+    /// Only funders approved by the pool are allowed.
+    /// Keeps the original encoding and accounts without the rewards root,
+    /// so the pause of the root applies once it is synced to the pool
     FillVault {
         /// Amount to fill
         amount: u64,
     },
 
-    /// Deposits amount of supply to the mining account
+    /// Deposits amount of supply to the mining account without a boost.
+    /// Keeps the original encoding and accounts without the rewards root,
+    /// so the pause of the root applies once it is synced to the pool
     DepositMining {
         /// Amount to deposit
         amount: u64,
     },
 
-    /// Withdraws amount of supply to the mining account
+    /// Withdraws the whole supply from the mining account and closes it.
+    /// Keeps the original encoding and accounts without the rewards root,
+    /// so the pause of the root applies once it is synced to the pool
     WithdrawMining,
 
-    /// Claims amount of rewards
+    /// Claims amount of rewards.
+    /// Keeps the original accounts without the rewards root,
+    /// so the pause of the root applies once it is synced to the pool.
+    /// Reward pool has to be writable
     Claim,
 
     /// Creates and initializes a reward root
     InitializeRoot,
 
-    /// Migrates reward pool
+    /// Rewrites reward pool of the previous layout, the rewards root has to be migrated first.
+    /// Takes vaults of the pool as remaining accounts, followed by co-signers.
+    /// Rejected since unclaimed rewards of the previous layout are unknown, use MigratePoolChecked
     MigratePool,
+
+    /// Approves funder of the reward pool vaults
+    AddFunder,
+
+    /// Revokes funder of the reward pool vaults
+    RemoveFunder,
+
+    /// Updates emission parameters of the vault
+    UpdateVault {
+        /// Reward ratio of deposit currency
+        ratio_base: u64,
+        /// Reward ratio of reward currency
+        ratio_quote: u64,
+        /// Time period for reward calculation
+        reward_period_sec: u32,
+        /// Time when reward destribution begins
+        distribution_starts_at: u64,
+        /// Time when reward distribution ends
+        distribution_ends_at: Option<u64>,
+        /// Maximum amount of reward per period (cap)
+        reward_max_amount_per_period: u64,
+    },
+
+    /// Ends distribution of the vault.
+    /// Accrued rewards can be claimed during the claim period
+    SunsetVault {
+        /// Time for users to claim accrued rewards
+        claim_period_sec: u64,
+    },
+
+    /// Sweeps leftovers of the ended vault, closes it and frees the slot
+    RemoveVault,
+
+    /// Proposes a new authority of the rewards root
+    ProposeRootAuthority,
+
+    /// Accepts authority of the rewards root by the proposed account
+    AcceptRootAuthority,
+
+    /// Updates reward pool parameters.
+    /// Existing deposits keep their lock time unless it's loosened
+    UpdatePool {
+        /// staking lock time
+        lock_time_sec: u64,
+        /// Cooldown of the requested withdrawals
+        unbonding_period_sec: u64,
+        /// Penalty of withdrawing locked amount in bps, zero for hard lock
+        early_exit_penalty_bps: u16,
+        /// Receiver of early exit penalties
+        penalty_destination: PenaltyDestination,
+        /// Limit of reward vaults
+        max_vaults: u8,
+    },
+
+    /// Moves amount to unbonding, it stops earning rewards immediately
+    RequestWithdraw {
+        /// Amount to unbond
+        amount: u64,
+    },
+
+    /// Releases unbonded amount after the unbonding period
+    FinalizeWithdraw,
+
+    /// Stakes unbonding amount back
+    CancelWithdraw,
+
+    /// Sets owner of treasury token accounts receiving early exit penalties
+    SetTreasury,
+
+    /// Sets account of the rewards root role
+    SetRole {
+        /// Role to set
+        role: Role,
+    },
+
+    /// Pauses or resumes operations of all pools of the root.
+    /// Legacy instructions of a pool are paused once the root pause is synced to it
+    SetRootPaused {
+        /// Pause flag
+        paused: bool,
+    },
+
+    /// Pauses or resumes operations of the pool
+    SetPoolPaused {
+        /// Pause flag
+        paused: bool,
+    },
+
+    /// Returns principal of the mining while the pool is paused.
+    /// Reward logic is skipped, unclaimed rewards are lost with the closed mining account
+    EmergencyWithdraw,
+
+    /// Queues admin action executable after the root timelock
+    ProposeAction {
+        /// Proposed action
+        action: AdminAction,
+    },
+
+    /// Executes queued admin action once its timelock has passed, callable by anyone
+    ExecuteAction,
+
+    /// Cancels queued admin action
+    CancelAction,
+
+    /// Creates M-of-N multisig usable as the root authority.
+    /// Admin instructions signed by a multisig member take the multisig and
+    /// co-signers as remaining accounts
+    InitializeMultisig {
+        /// Keys allowed to sign
+        signers: Vec<Pubkey>,
+        /// Number of signatures required
+        threshold: u8,
+    },
+
+    /// Switches the pool to managed mode, where the deposit authority adjusts balances
+    /// without token transfers. Unsets it when no deposit authority is passed
+    SetDepositAuthority {
+        /// Program account managing balances
+        deposit_authority: Option<Pubkey>,
+    },
+
+    /// Increases mining balance of the managed pool, called by the deposit authority
+    ManagedDeposit {
+        /// Amount to deposit
+        amount: u64,
+    },
+
+    /// Decreases mining balance of the managed pool, called by the deposit authority
+    ManagedWithdraw {
+        /// Amount to withdraw
+        amount: u64,
+    },
+
+    /// Moves staked amount to the mining of another owner, creating it if needed.
+    /// Locked tranches and a proportional share of unclaimed rewards are moved with it,
+    /// so the destination owner has to sign
+    TransferMining {
+        /// Amount to transfer
+        amount: u64,
+    },
+
+    /// Creates receipt mint of the pool, minted 1:1 on deposit and burned on withdraw.
+    /// Pool must have no stakers and no locks, unbonding or early exit penalty
+    InitializeReceiptMint,
+
+    /// Sets mining amount to the receipt balance of its owner, callable by anyone.
+    /// Rewards since the last settlement accrue to the lesser of the previous amount
+    /// and the balance
+    SettleReceipt,
+
+    /// Rewrites rewards root of the previous layout
+    MigrateRoot,
+
+    /// Withdraws amount of supply from the mining account.
+    /// The mining account is closed once its balance reaches zero.
+    /// Treasury token account follows when early exit penalty goes to treasury
+    /// or there are no other stakers to share it with,
+    /// receipt mint and receipt account - when the pool has receipt mint.
+    WithdrawMiningPartial {
+        /// Amount to withdraw
+        amount: u64,
+        /// Allow closing the mining account with unclaimed rewards
+        forfeit: bool,
+    },
+
+    /// Creates a new vault account and adds it to the reward pool
+    AddVaultExtended {
+        /// Reward ratio of deposit currency
+        ratio_base: u64,
+        /// Reward ratio of reward currency
+        ratio_quote: u64,
+        /// Time period for reward calculation
+        reward_period_sec: u32,
+        /// Time when reward destribution begins
+        distribution_starts_at: u64,
+        /// Time when reward distribution ends
+        distribution_ends_at: Option<u64>,
+        /// Maximum amount of reward per period (cap)
+        reward_max_amount_per_period: u64,
+        /// Reward distribution mode
+        distribution_mode: DistributionMode,
+    },
+
+    /// Fills the reward pool with rewards.
+    /// Only approved funders are allowed
+    FillVaultChecked {
+        /// Amount to fill
+        amount: u64,
+    },
+
+    /// Claims amount of rewards.
+    /// Receipt mint and receipt account follow when the pool has receipt mint
+    ClaimChecked,
+
+    /// Deposits amount of supply to the mining account.
+    /// Optional lock period boosts the stake up to 4x at one year.
+    /// Receipt mint and receipt account follow when the pool has receipt mint
+    DepositMiningLocked {
+        /// Amount to deposit
+        amount: u64,
+        /// Lock period of the stake boost, zero for no boost
+        lock_period_sec: u64,
+    },
+
+    /// Rewrites reward pool of the previous layout, the rewards root has to be migrated first.
+    /// Takes vaults of the pool as remaining accounts, followed by co-signers.
+    /// Liabilities are reserved for minings of the previous layout until they are converted,
+    /// rewards owed over the reserve are taken from the vault budget
+    MigratePoolChecked {
+        /// Unclaimed rewards of the previous layout up to the migration for every vault
+        /// of the pool
+        liabilities: Vec<u64>,
+    },
+
+    /// Claims rewards of every passed vault.
+    /// Takes (vault, user reward token account) pairs as remaining accounts,
+    /// receipt mint and receipt account follow when the pool has receipt mint
+    ClaimAll,
+
+    /// Syncs pause of the rewards root to the pool, callable by anyone.
+    /// Legacy instructions without the rewards root are rejected while the synced root is paused
+    SyncRootPaused,
+
+    /// Converts mining of the previous layout, callable by anyone.
+    /// Rewards owed up to the pool migration are taken from the reserve of its vaults
+    /// and the lock time of the pool at the migration is kept for its deposit
+    MigrateMining,
 }
 
 /// Creates 'InitializePool' instruction.
@@ -125,7 +367,6 @@ pub fn add_vault(
 }
 
 /// Creates 'FillVault' instruction.
-#[allow(clippy::too_many_arguments)]
 pub fn fill_vault(
     program_id: &Pubkey,
     reward_pool: &Pubkey,
@@ -230,7 +471,7 @@ pub fn claim(
     println!("user_reward_token: {}", user_reward_token);
 
     let accounts = vec![
-        AccountMeta::new_readonly(*reward_pool, false),
+        AccountMeta::new(*reward_pool, false),
         AccountMeta::new_readonly(*reward_mint, false),
         AccountMeta::new(*vault, false),
         AccountMeta::new(*mining, false),
@@ -268,8 +509,9 @@ pub fn migrate_pool(
     reward_pool: &Pubkey,
     payer: &Pubkey,
     liquidity_mint: &Pubkey,
+    reward_mints: &[Pubkey],
 ) -> Instruction {
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new_readonly(*root_account, false),
         AccountMeta::new(*reward_pool, false),
         AccountMeta::new_readonly(*liquidity_mint, false),
@@ -278,5 +520,964 @@ pub fn migrate_pool(
         AccountMeta::new_readonly(sysvar::rent::id(), false),
     ];
 
+    for reward_mint in reward_mints {
+        let (vault, _) = find_vault_program_address(program_id, reward_pool, reward_mint);
+        accounts.push(AccountMeta::new_readonly(vault, false));
+    }
+
     Instruction::new_with_borsh(*program_id, &RewardsInstruction::MigratePool, accounts)
 }
+
+/// Creates 'AddFunder' instruction.
+pub fn add_funder(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    authority: &Pubkey,
+    funder: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*funder, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::AddFunder, accounts)
+}
+
+/// Creates 'RemoveFunder' instruction.
+pub fn remove_funder(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    authority: &Pubkey,
+    funder: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*funder, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::RemoveFunder, accounts)
+}
+
+/// Creates 'UpdateVault' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn update_vault(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    authority: &Pubkey,
+    ratio_base: u64,
+    ratio_quote: u64,
+    reward_period_sec: u32,
+    distribution_starts_at: u64,
+    distribution_ends_at: Option<u64>,
+    reward_max_amount_per_period: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::UpdateVault {
+            ratio_base,
+            ratio_quote,
+            reward_period_sec,
+            distribution_starts_at,
+            distribution_ends_at,
+            reward_max_amount_per_period,
+        },
+        accounts,
+    )
+}
+
+/// Creates 'SunsetVault' instruction.
+pub fn sunset_vault(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    authority: &Pubkey,
+    claim_period_sec: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::SunsetVault { claim_period_sec },
+        accounts,
+    )
+}
+
+/// Creates 'RemoveVault' instruction.
+pub fn remove_vault(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    let (vault, _) = find_vault_program_address(program_id, reward_pool, reward_mint);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::RemoveVault, accounts)
+}
+
+/// Creates 'ProposeRootAuthority' instruction.
+pub fn propose_root_authority(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*rewards_root, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*new_authority, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::ProposeRootAuthority,
+        accounts,
+    )
+}
+
+/// Creates 'AcceptRootAuthority' instruction.
+pub fn accept_root_authority(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*rewards_root, false),
+        AccountMeta::new_readonly(*new_authority, true),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::AcceptRootAuthority,
+        accounts,
+    )
+}
+
+/// Creates 'UpdatePool' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn update_pool(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    authority: &Pubkey,
+    lock_time_sec: u64,
+    unbonding_period_sec: u64,
+    early_exit_penalty_bps: u16,
+    penalty_destination: PenaltyDestination,
+    max_vaults: u8,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*authority, true),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::UpdatePool {
+            lock_time_sec,
+            unbonding_period_sec,
+            early_exit_penalty_bps,
+            penalty_destination,
+            max_vaults,
+        },
+        accounts,
+    )
+}
+
+/// Creates 'RequestWithdraw' instruction.
+pub fn request_withdraw(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    mining: &Pubkey,
+    user: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::RequestWithdraw { amount },
+        accounts,
+    )
+}
+
+/// Creates 'FinalizeWithdraw' instruction.
+pub fn finalize_withdraw(
+    program_id: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_pool_spl: &Pubkey,
+    reward_pool_authority: &Pubkey,
+    mining: &Pubkey,
+    user_token_account: &Pubkey,
+    user: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*reward_pool, false),
+        AccountMeta::new(*reward_pool_spl, false),
+        AccountMeta::new_readonly(*reward_pool_authority, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::FinalizeWithdraw, accounts)
+}
+
+/// Creates 'CancelWithdraw' instruction.
+pub fn cancel_withdraw(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    mining: &Pubkey,
+    user: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::CancelWithdraw, accounts)
+}
+
+/// Creates 'SetTreasury' instruction.
+pub fn set_treasury(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    authority: &Pubkey,
+    treasury: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*rewards_root, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*treasury, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::SetTreasury, accounts)
+}
+
+/// Creates 'SetRole' instruction.
+pub fn set_role(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    authority: &Pubkey,
+    account: &Pubkey,
+    role: Role,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*rewards_root, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*account, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::SetRole { role }, accounts)
+}
+
+/// Creates 'SetRootPaused' instruction.
+pub fn set_root_paused(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    pauser: &Pubkey,
+    paused: bool,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*rewards_root, false),
+        AccountMeta::new_readonly(*pauser, true),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::SetRootPaused { paused },
+        accounts,
+    )
+}
+
+/// Creates 'SetPoolPaused' instruction.
+pub fn set_pool_paused(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    pauser: &Pubkey,
+    paused: bool,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*pauser, true),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::SetPoolPaused { paused },
+        accounts,
+    )
+}
+
+/// Creates 'EmergencyWithdraw' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn emergency_withdraw(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_pool_spl: &Pubkey,
+    reward_pool_authority: &Pubkey,
+    mining: &Pubkey,
+    user_token_account: &Pubkey,
+    user: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*reward_pool_spl, false),
+        AccountMeta::new_readonly(*reward_pool_authority, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::EmergencyWithdraw,
+        accounts,
+    )
+}
+
+/// Creates 'ProposeAction' instruction.
+pub fn propose_action(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    pending_action: &Pubkey,
+    authority: &Pubkey,
+    action: AdminAction,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*rewards_root, false),
+        AccountMeta::new(*pending_action, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::ProposeAction { action },
+        accounts,
+    )
+}
+
+/// Creates 'ExecuteAction' instruction.
+pub fn execute_action(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    pending_action: &Pubkey,
+    payer: &Pubkey,
+    action: &AdminAction,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*rewards_root, false),
+        AccountMeta::new(*pending_action, false),
+        AccountMeta::new(*payer, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    if let Some(reward_pool) = action.reward_pool() {
+        accounts.push(AccountMeta::new(*reward_pool, false));
+    }
+
+    if let AdminAction::RemoveVault {
+        reward_pool,
+        reward_mint,
+        destination,
+    } = action
+    {
+        let (vault, _) = find_vault_program_address(program_id, reward_pool, reward_mint);
+
+        accounts.push(AccountMeta::new(vault, false));
+        accounts.push(AccountMeta::new(*destination, false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::ExecuteAction, accounts)
+}
+
+/// Creates 'CancelAction' instruction.
+pub fn cancel_action(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    pending_action: &Pubkey,
+    payer: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*pending_action, false),
+        AccountMeta::new(*payer, false),
+        AccountMeta::new_readonly(*authority, true),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::CancelAction, accounts)
+}
+
+/// Creates 'InitializeMultisig' instruction.
+pub fn initialize_multisig(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    payer: &Pubkey,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*multisig, true),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::InitializeMultisig { signers, threshold },
+        accounts,
+    )
+}
+
+/// Appends receipt accounts to the instruction of the pool with receipt mint.
+pub fn with_receipt(
+    mut instruction: Instruction,
+    receipt_mint: &Pubkey,
+    receipt_account: &Pubkey,
+) -> Instruction {
+    instruction.accounts.extend([
+        AccountMeta::new(*receipt_mint, false),
+        AccountMeta::new(*receipt_account, false),
+    ]);
+
+    instruction
+}
+
+/// Appends multisig authority and its co-signers to the admin instruction.
+pub fn with_multisig(
+    mut instruction: Instruction,
+    multisig: &Pubkey,
+    co_signers: &[&Pubkey],
+) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*multisig, false));
+    instruction.accounts.extend(
+        co_signers
+            .iter()
+            .map(|co_signer| AccountMeta::new_readonly(**co_signer, true)),
+    );
+
+    instruction
+}
+
+/// Creates 'SetDepositAuthority' instruction.
+pub fn set_deposit_authority(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    authority: &Pubkey,
+    deposit_authority: Option<Pubkey>,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*authority, true),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::SetDepositAuthority { deposit_authority },
+        accounts,
+    )
+}
+
+/// Creates 'ManagedDeposit' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn managed_deposit(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    mining: &Pubkey,
+    user: &Pubkey,
+    deposit_authority: &Pubkey,
+    payer: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new_readonly(*user, false),
+        AccountMeta::new_readonly(*deposit_authority, true),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::ManagedDeposit { amount },
+        accounts,
+    )
+}
+
+/// Creates 'ManagedWithdraw' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn managed_withdraw(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    mining: &Pubkey,
+    user: &Pubkey,
+    deposit_authority: &Pubkey,
+    payer: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new_readonly(*user, false),
+        AccountMeta::new_readonly(*deposit_authority, true),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::ManagedWithdraw { amount },
+        accounts,
+    )
+}
+
+/// Creates 'TransferMining' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_mining(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    mining: &Pubkey,
+    destination_mining: &Pubkey,
+    user: &Pubkey,
+    destination_user: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*destination_mining, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(*destination_user, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::TransferMining { amount },
+        accounts,
+    )
+}
+
+/// Creates 'InitializeReceiptMint' instruction.
+pub fn initialize_receipt_mint(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    receipt_mint: &Pubkey,
+    liquidity_mint: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*receipt_mint, false),
+        AccountMeta::new_readonly(*liquidity_mint, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::InitializeReceiptMint,
+        accounts,
+    )
+}
+
+/// Creates 'SettleReceipt' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn settle_receipt(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    mining: &Pubkey,
+    user: &Pubkey,
+    payer: &Pubkey,
+    receipt_mint: &Pubkey,
+    receipt_account: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new_readonly(*user, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    with_receipt(
+        Instruction::new_with_borsh(*program_id, &RewardsInstruction::SettleReceipt, accounts),
+        receipt_mint,
+        receipt_account,
+    )
+}
+
+/// Creates 'MigrateRoot' instruction.
+pub fn migrate_root(program_id: &Pubkey, rewards_root: &Pubkey, authority: &Pubkey) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*rewards_root, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::MigrateRoot, accounts)
+}
+
+/// Creates 'WithdrawMiningPartial' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_mining_partial(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_pool_spl: &Pubkey,
+    reward_pool_authority: &Pubkey,
+    liquidity_mint: &Pubkey,
+    mining: &Pubkey,
+    user_token_account: &Pubkey,
+    user: &Pubkey,
+    amount: u64,
+    forfeit: bool,
+    treasury_token_account: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*reward_pool_spl, false),
+        AccountMeta::new_readonly(*reward_pool_authority, false),
+        AccountMeta::new_readonly(*liquidity_mint, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    if let Some(treasury_token_account) = treasury_token_account {
+        accounts.push(AccountMeta::new(*treasury_token_account, false));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::WithdrawMiningPartial { amount, forfeit },
+        accounts,
+    )
+}
+
+/// Creates 'AddVaultExtended' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn add_vault_extended(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    vault: &Pubkey,
+    payer: &Pubkey,
+    ratio_base: u64,
+    ratio_quote: u64,
+    reward_period_sec: u32,
+    distribution_starts_at: u64,
+    distribution_ends_at: Option<u64>,
+    reward_max_amount_per_period: u64,
+    distribution_mode: DistributionMode,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(*vault, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::AddVaultExtended {
+            ratio_base,
+            ratio_quote,
+            reward_period_sec,
+            distribution_starts_at,
+            distribution_ends_at,
+            reward_max_amount_per_period,
+            distribution_mode,
+        },
+        accounts,
+    )
+}
+
+/// Creates 'FillVaultChecked' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn fill_vault_checked(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    vault: &Pubkey,
+    from: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(*vault, false),
+        AccountMeta::new(*from, false),
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::FillVaultChecked { amount },
+        accounts,
+    )
+}
+
+/// Creates 'ClaimChecked' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_checked(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    vault: &Pubkey,
+    mining: &Pubkey,
+    user: &Pubkey,
+    user_reward_token: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(*vault, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*user_reward_token, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::ClaimChecked, accounts)
+}
+
+/// Creates 'DepositMiningLocked' instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_mining_locked(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    reward_pool_spl: &Pubkey,
+    liquidity_mint: &Pubkey,
+    mining: &Pubkey,
+    user_token_account: &Pubkey,
+    user: &Pubkey,
+    amount: u64,
+    lock_period_sec: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*reward_pool_spl, false),
+        AccountMeta::new_readonly(*liquidity_mint, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::DepositMiningLocked {
+            amount,
+            lock_period_sec,
+        },
+        accounts,
+    )
+}
+
+/// Creates 'MigratePoolChecked' instruction.
+pub fn migrate_pool_checked(
+    program_id: &Pubkey,
+    root_account: &Pubkey,
+    reward_pool: &Pubkey,
+    payer: &Pubkey,
+    liquidity_mint: &Pubkey,
+    reward_mints: &[Pubkey],
+    liabilities: Vec<u64>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*root_account, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new_readonly(*liquidity_mint, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    for reward_mint in reward_mints {
+        let (vault, _) = find_vault_program_address(program_id, reward_pool, reward_mint);
+        accounts.push(AccountMeta::new_readonly(vault, false));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &RewardsInstruction::MigratePoolChecked { liabilities },
+        accounts,
+    )
+}
+
+/// Creates 'ClaimAll' instruction.
+/// Each pair consists of reward mint and user reward token account.
+pub fn claim_all(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+    mining: &Pubkey,
+    user: &Pubkey,
+    rewards: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    for (reward_mint, user_reward_token) in rewards {
+        let (vault, _) = find_vault_program_address(program_id, reward_pool, reward_mint);
+
+        accounts.push(AccountMeta::new(vault, false));
+        accounts.push(AccountMeta::new(*user_reward_token, false));
+    }
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::ClaimAll, accounts)
+}
+
+/// Creates 'SyncRootPaused' instruction.
+pub fn sync_root_paused(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    reward_pool: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*rewards_root, false),
+        AccountMeta::new(*reward_pool, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::SyncRootPaused, accounts)
+}
+
+/// Creates 'MigrateMining' instruction.
+pub fn migrate_mining(
+    program_id: &Pubkey,
+    reward_pool: &Pubkey,
+    mining: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*reward_pool, false),
+        AccountMeta::new(*mining, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &RewardsInstruction::MigrateMining, accounts)
+}
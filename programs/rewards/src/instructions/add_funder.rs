@@ -0,0 +1,63 @@
+use crate::state::{RewardPool, RewardsRoot, Role};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Instruction context
+pub struct AddFunderContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    funder: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> AddFunderContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<AddFunderContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let authority = AccountLoader::next_signer(account_info_iter)?;
+        let funder = AccountLoader::next_unchecked(account_info_iter)?;
+
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(AddFunderContext {
+            rewards_root,
+            reward_pool,
+            authority,
+            funder,
+            co_signers,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+
+        {
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            rewards_root.assert_role(
+                program_id,
+                Role::VaultManager,
+                self.authority,
+                self.co_signers,
+            )?;
+        }
+
+        reward_pool.add_funder(*self.funder.key)?;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@ use solana_program::system_program;
 use solana_program::sysvar::{clock, Sysvar, SysvarId};
 use spl_token::state::Account;
 
-use crate::state::{RewardPool, RewardVault, RewardsRoot};
+use crate::state::{DistributionMode, RewardPool, RewardVault, RewardsRoot, Role};
 
 /// Instruction context
 pub struct AddVaultContext<'a, 'b> {
@@ -22,6 +22,7 @@ pub struct AddVaultContext<'a, 'b> {
     payer: &'a AccountInfo<'b>,
     clock: &'a AccountInfo<'b>,
     rent: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
 }
 
 impl<'a, 'b> AddVaultContext<'a, 'b> {
@@ -43,6 +44,8 @@ impl<'a, 'b> AddVaultContext<'a, 'b> {
         let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
         let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
 
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
         Ok(AddVaultContext {
             rewards_root,
             reward_pool,
@@ -51,10 +54,12 @@ impl<'a, 'b> AddVaultContext<'a, 'b> {
             payer,
             clock,
             rent,
+            co_signers,
         })
     }
 
     /// Process instruction
+    #[allow(clippy::too_many_arguments)]
     pub fn process(
         &self,
         program_id: &Pubkey,
@@ -62,14 +67,21 @@ impl<'a, 'b> AddVaultContext<'a, 'b> {
         ratio_quote: u64,
         reward_period_sec: u32,
         distribution_starts_at: u64,
+        distribution_ends_at: Option<u64>,
         reward_max_amount_per_period: u64,
+        distribution_mode: DistributionMode,
     ) -> ProgramResult {
         let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
         assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
 
         {
             let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
-            assert_account_key(self.payer, &rewards_root.authority)?;
+            rewards_root.assert_role(
+                program_id,
+                Role::VaultManager,
+                self.payer,
+                self.co_signers,
+            )?;
         }
 
         let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp;
@@ -77,6 +89,18 @@ impl<'a, 'b> AddVaultContext<'a, 'b> {
             return Err(ProgramError::InvalidArgument);
         }
 
+        if matches!(distribution_ends_at, Some(ends_at) if ends_at <= distribution_starts_at) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let invalid_emission = match distribution_mode {
+            DistributionMode::FixedRatio => ratio_base == 0,
+            DistributionMode::Proportional => reward_max_amount_per_period == 0,
+        };
+        if reward_period_sec == 0 || invalid_emission {
+            return Err(ProgramError::InvalidArgument);
+        }
+
         let bump = self.create_spl_acc(program_id)?;
 
         reward_pool.add_vault(RewardVault {
@@ -86,10 +110,24 @@ impl<'a, 'b> AddVaultContext<'a, 'b> {
             reward_period_sec,
             reward_mint: *self.reward_mint.key,
             distribution_starts_at,
+            distribution_ends_at,
             reward_max_amount_per_period,
+            distribution_mode,
+            index_with_precision: 0,
+            capped_index_with_precision: 0,
+            cap_index_with_precision: 0,
+            index_updated_at: distribution_starts_at,
+            total_funded: 0,
+            total_claimed: 0,
+            total_accrued: 0,
+            ended_at: 0,
+            claim_deadline: 0,
+            // assigned by the pool
+            id: 0,
+            legacy_reserve: 0,
         })?;
 
-        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        reward_pool.save(self.reward_pool, self.payer)?;
 
         Ok(())
     }
@@ -0,0 +1,58 @@
+use crate::state::{PendingAction, RewardsRoot};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Instruction context
+pub struct CancelActionContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    pending_action: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> CancelActionContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<CancelActionContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let pending_action = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let payer = AccountLoader::next_unchecked(account_info_iter)?;
+        let authority = AccountLoader::next_signer(account_info_iter)?;
+
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(CancelActionContext {
+            rewards_root,
+            pending_action,
+            payer,
+            authority,
+            co_signers,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let pending_action = PendingAction::unpack(&self.pending_action.data.borrow())?;
+        assert_account_key(self.rewards_root, &pending_action.rewards_root)?;
+        assert_account_key(self.payer, &pending_action.payer)?;
+
+        {
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            rewards_root.assert_authority(program_id, self.authority, self.co_signers)?;
+        }
+
+        msg!("Action #{} cancelled", pending_action.id);
+
+        everlend_utils::cpi::system::close_account(self.pending_action, self.payer)
+    }
+}
@@ -1,4 +1,5 @@
-use crate::state::{Mining, RewardPool};
+use crate::instructions::ReceiptAccounts;
+use crate::state::{Mining, RewardPool, RewardsRoot};
 use everlend_utils::{assert_account_key, AccountLoader, EverlendError};
 use solana_program::account_info::AccountInfo;
 use solana_program::clock::Clock;
@@ -13,6 +14,7 @@ use spl_token::state::Account;
 
 /// Instruction context
 pub struct ClaimContext<'a, 'b> {
+    rewards_root: Option<&'a AccountInfo<'b>>,
     reward_pool: &'a AccountInfo<'b>,
     reward_mint: &'a AccountInfo<'b>,
     vault: &'a AccountInfo<'b>,
@@ -21,6 +23,7 @@ pub struct ClaimContext<'a, 'b> {
     user_reward_token_account: &'a AccountInfo<'b>,
     clock: &'a AccountInfo<'b>,
     rent: &'a AccountInfo<'b>,
+    receipt_accounts: &'a [AccountInfo<'b>],
 }
 
 impl<'a, 'b> ClaimContext<'a, 'b> {
@@ -28,9 +31,33 @@ impl<'a, 'b> ClaimContext<'a, 'b> {
     pub fn new(
         program_id: &Pubkey,
         accounts: &'a [AccountInfo<'b>],
+    ) -> Result<ClaimContext<'a, 'b>, ProgramError> {
+        Self::load(program_id, accounts, true)
+    }
+
+    /// New instruction context of the legacy accounts without the rewards root
+    pub fn new_legacy(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<ClaimContext<'a, 'b>, ProgramError> {
+        Self::load(program_id, accounts, false)
+    }
+
+    fn load(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+        with_root: bool,
     ) -> Result<ClaimContext<'a, 'b>, ProgramError> {
         let account_info_iter = &mut accounts.iter().enumerate();
 
+        let rewards_root = if with_root {
+            Some(AccountLoader::next_with_owner(
+                account_info_iter,
+                program_id,
+            )?)
+        } else {
+            None
+        };
         let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
         let reward_mint = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
         let vault = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
@@ -43,7 +70,10 @@ impl<'a, 'b> ClaimContext<'a, 'b> {
         let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
         let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
 
+        let receipt_accounts = AccountLoader::remaining(accounts, account_info_iter);
+
         Ok(ClaimContext {
+            rewards_root,
             reward_pool,
             reward_mint,
             vault,
@@ -52,15 +82,25 @@ impl<'a, 'b> ClaimContext<'a, 'b> {
             user_reward_token_account,
             clock,
             rent,
+            receipt_accounts,
         })
     }
 
     /// Process instruction
     pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
         let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp;
-        let reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
         let mut mining = Mining::unpack(&self.mining.data.borrow())?;
 
+        match self.rewards_root {
+            Some(rewards_root) => {
+                assert_account_key(rewards_root, &reward_pool.rewards_root)?;
+                let rewards_root = RewardsRoot::unpack(&rewards_root.data.borrow())?;
+                reward_pool.assert_not_paused(&rewards_root)?;
+            }
+            None => reward_pool.assert_pool_not_paused()?,
+        }
+
         {
             let mining_pubkey = Pubkey::create_program_address(
                 &[
@@ -109,12 +149,22 @@ impl<'a, 'b> ClaimContext<'a, 'b> {
             )?;
         }
 
-        mining.refresh_rewards(reward_pool.vaults.iter(), timestamp as u64)?;
-        let reward_amount = mining.flush_rewards(*self.reward_mint.key);
+        // stake follows receipts transferred since the last settlement
+        if let Some(receipts) = ReceiptAccounts::load(&reward_pool, self.receipt_accounts)? {
+            let balance = receipts.balance(program_id, self.mining.key, self.user.key)?;
+            reward_pool.settle(&mut mining, balance, timestamp as u64)?;
+        }
+
+        reward_pool.refresh_mining(&mut mining, timestamp as u64)?;
+
+        // underfunded vault pays what is available
+        let vault_amount = Account::unpack(&self.vault.data.borrow())?.amount;
+        let reward_amount = reward_pool.claim(&mut mining, *self.reward_mint.key, vault_amount)?;
 
         self.spl_transfer_reward(reward_amount, reward_pool_seeds)?;
 
-        Mining::pack(mining, *self.mining.data.borrow_mut())?;
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        mining.save(self.mining, self.user)?;
 
         Ok(())
     }
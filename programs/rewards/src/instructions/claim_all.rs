@@ -0,0 +1,174 @@
+use crate::instructions::ReceiptAccounts;
+use crate::state::{Mining, RewardPool, RewardsRoot};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program::sysvar::{clock, Sysvar};
+use spl_token::state::Account;
+
+/// Instruction context
+pub struct ClaimAllContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    mining: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+    /// Pairs of vault and user reward token account,
+    /// followed by receipt accounts of the pool with receipt mint
+    remaining_accounts: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> ClaimAllContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<ClaimAllContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let mining = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let user = AccountLoader::next_signer(account_info_iter)?;
+        let _token_program = AccountLoader::next_with_key(account_info_iter, &spl_token::id())?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+
+        let remaining_accounts = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(ClaimAllContext {
+            rewards_root,
+            reward_pool,
+            mining,
+            user,
+            clock,
+            remaining_accounts,
+        })
+    }
+
+    /// Load pairs of vault and user reward token account
+    fn load_vaults(
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<Vec<(&'a AccountInfo<'b>, &'a AccountInfo<'b>)>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let mut vaults = Vec::new();
+        while AccountLoader::has_more(account_info_iter) {
+            let vault = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+            let user_reward_token_account =
+                AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+
+            vaults.push((vault, user_reward_token_account));
+        }
+
+        Ok(vaults)
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp;
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        let mut mining = Mining::unpack(&self.mining.data.borrow())?;
+
+        {
+            assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            reward_pool.assert_not_paused(&rewards_root)?;
+        }
+
+        {
+            let mining_pubkey = Pubkey::create_program_address(
+                &[
+                    b"mining".as_ref(),
+                    self.user.key.as_ref(),
+                    self.reward_pool.key.as_ref(),
+                    &[mining.bump],
+                ],
+                program_id,
+            )?;
+            assert_account_key(self.mining, &mining_pubkey)?;
+        }
+
+        let reward_pool_seeds = &[
+            b"reward_pool".as_ref(),
+            &reward_pool.rewards_root.to_bytes()[..32],
+            &reward_pool.liquidity_mint.to_bytes()[..32],
+            &[reward_pool.bump],
+        ];
+
+        {
+            assert_account_key(self.user, &mining.owner)?;
+            assert_account_key(self.reward_pool, &mining.reward_pool)?;
+            assert_account_key(
+                self.reward_pool,
+                &Pubkey::create_program_address(reward_pool_seeds, program_id)?,
+            )?;
+        }
+
+        let receipt_accounts_len = match reward_pool.receipt_mint {
+            Some(_) => 2,
+            None => 0,
+        };
+        let (vault_accounts, receipt_accounts) = self.remaining_accounts.split_at(
+            self.remaining_accounts
+                .len()
+                .checked_sub(receipt_accounts_len)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?,
+        );
+        let vaults = Self::load_vaults(vault_accounts)?;
+
+        // stake follows receipts transferred since the last settlement
+        if let Some(receipts) = ReceiptAccounts::load(&reward_pool, receipt_accounts)? {
+            let balance = receipts.balance(program_id, self.mining.key, self.user.key)?;
+            reward_pool.settle(&mut mining, balance, timestamp as u64)?;
+        }
+
+        reward_pool.refresh_mining(&mut mining, timestamp as u64)?;
+
+        for (vault, user_reward_token_account) in vaults.iter() {
+            let reward_mint = reward_pool
+                .vaults
+                .iter()
+                .find(|v| {
+                    Pubkey::create_program_address(
+                        &[
+                            b"vault".as_ref(),
+                            &self.reward_pool.key.to_bytes()[..32],
+                            &v.reward_mint.to_bytes()[..32],
+                            &[v.bump],
+                        ],
+                        program_id,
+                    )
+                    .is_ok_and(|vault_pubkey| vault_pubkey.eq(vault.key))
+                })
+                .ok_or(ProgramError::InvalidArgument)?
+                .reward_mint;
+
+            // underfunded vault pays what is available
+            let vault_amount = Account::unpack(&vault.data.borrow())?.amount;
+            let reward_amount = reward_pool.claim(&mut mining, reward_mint, vault_amount)?;
+            if reward_amount == 0 {
+                continue;
+            }
+
+            everlend_utils::cpi::spl_token::transfer(
+                (*vault).clone(),
+                (*user_reward_token_account).clone(),
+                self.reward_pool.clone(),
+                reward_amount,
+                &[reward_pool_seeds],
+            )?;
+        }
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        mining.save(self.mining, self.user)?;
+
+        Ok(())
+    }
+}
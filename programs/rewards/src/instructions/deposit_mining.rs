@@ -1,6 +1,7 @@
-use crate::state::{Mining, RewardPool};
+use crate::instructions::ReceiptAccounts;
+use crate::state::{Mining, RewardPool, RewardsRoot};
 use crate::{find_mining_program_address, find_reward_pool_spl_program_address};
-use everlend_utils::{assert_account_key, AccountLoader};
+use everlend_utils::{assert_account_key, AccountLoader, EverlendError};
 use solana_program::account_info::AccountInfo;
 use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
@@ -13,6 +14,7 @@ use solana_program::sysvar::{clock, Sysvar, SysvarId};
 
 /// Instruction context
 pub struct DepositMiningContext<'a, 'b> {
+    rewards_root: Option<&'a AccountInfo<'b>>,
     reward_pool: &'a AccountInfo<'b>,
     reward_pool_spl: &'a AccountInfo<'b>,
     liquidity_mint: &'a AccountInfo<'b>,
@@ -21,6 +23,7 @@ pub struct DepositMiningContext<'a, 'b> {
     user: &'a AccountInfo<'b>,
     clock: &'a AccountInfo<'b>,
     rent: &'a AccountInfo<'b>,
+    receipt_accounts: &'a [AccountInfo<'b>],
 }
 
 impl<'a, 'b> DepositMiningContext<'a, 'b> {
@@ -28,8 +31,32 @@ impl<'a, 'b> DepositMiningContext<'a, 'b> {
     pub fn new(
         program_id: &Pubkey,
         accounts: &'a [AccountInfo<'b>],
+    ) -> Result<DepositMiningContext<'a, 'b>, ProgramError> {
+        Self::load(program_id, accounts, true)
+    }
+
+    /// New instruction context of the legacy accounts without the rewards root
+    pub fn new_legacy(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<DepositMiningContext<'a, 'b>, ProgramError> {
+        Self::load(program_id, accounts, false)
+    }
+
+    fn load(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+        with_root: bool,
     ) -> Result<DepositMiningContext<'a, 'b>, ProgramError> {
         let account_info_iter = &mut accounts.iter().enumerate();
+        let rewards_root = if with_root {
+            Some(AccountLoader::next_with_owner(
+                account_info_iter,
+                program_id,
+            )?)
+        } else {
+            None
+        };
         let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
         let reward_pool_spl = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
         let liquidity_mint = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
@@ -44,7 +71,10 @@ impl<'a, 'b> DepositMiningContext<'a, 'b> {
         let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
         let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
 
+        let receipt_accounts = AccountLoader::remaining(accounts, account_info_iter);
+
         Ok(DepositMiningContext {
+            rewards_root,
             reward_pool,
             reward_pool_spl,
             liquidity_mint,
@@ -53,11 +83,12 @@ impl<'a, 'b> DepositMiningContext<'a, 'b> {
             user,
             clock,
             rent,
+            receipt_accounts,
         })
     }
 
     /// Process instruction
-    pub fn process(&self, program_id: &Pubkey, amount: u64) -> ProgramResult {
+    pub fn process(&self, program_id: &Pubkey, amount: u64, lock_period_sec: u64) -> ProgramResult {
         let mut mining = self.check_and_init_mining(program_id)?;
         {
             let mining_pubkey = Pubkey::create_program_address(
@@ -75,6 +106,18 @@ impl<'a, 'b> DepositMiningContext<'a, 'b> {
         }
 
         let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        {
+            match self.rewards_root {
+                Some(rewards_root) => {
+                    assert_account_key(rewards_root, &reward_pool.rewards_root)?;
+                    let rewards_root = RewardsRoot::unpack(&rewards_root.data.borrow())?;
+                    reward_pool.assert_not_paused(&rewards_root)?;
+                }
+                None => reward_pool.assert_pool_not_paused()?,
+            }
+            reward_pool.assert_not_managed()?;
+        }
+
         {
             let (spl_pubkey, _) = find_reward_pool_spl_program_address(
                 program_id,
@@ -87,6 +130,24 @@ impl<'a, 'b> DepositMiningContext<'a, 'b> {
 
         let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp;
 
+        let receipts = ReceiptAccounts::load(&reward_pool, self.receipt_accounts)?;
+        if let Some(receipts) = &receipts {
+            // receipts are freely transferable, so the stake can't be locked
+            if lock_period_sec > 0 {
+                return Err(EverlendError::ReceiptPool.into());
+            }
+
+            receipts.check_and_init(
+                program_id,
+                self.mining.key,
+                self.user,
+                self.user,
+                self.rent,
+            )?;
+            let balance = receipts.balance(program_id, self.mining.key, self.user.key)?;
+            reward_pool.settle(&mut mining, balance, timestamp as u64)?;
+        }
+
         // Transfer token from source to token account
         everlend_utils::cpi::spl_token::transfer(
             self.user_token_account.clone(),
@@ -96,10 +157,21 @@ impl<'a, 'b> DepositMiningContext<'a, 'b> {
             &[],
         )?;
 
-        reward_pool.deposit(&mut mining, amount, timestamp as u64)?;
+        reward_pool.deposit(&mut mining, amount, lock_period_sec, timestamp as u64)?;
+
+        if let Some(receipts) = &receipts {
+            let reward_pool_seeds = &[
+                b"reward_pool".as_ref(),
+                reward_pool.rewards_root.as_ref(),
+                reward_pool.liquidity_mint.as_ref(),
+                &[reward_pool.bump],
+            ];
+
+            receipts.mint(self.reward_pool, reward_pool_seeds, amount)?;
+        }
 
         RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
-        Mining::pack(mining, *self.mining.data.borrow_mut())?;
+        mining.save(self.mining, self.user)?;
 
         Ok(())
     }
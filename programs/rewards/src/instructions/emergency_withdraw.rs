@@ -0,0 +1,197 @@
+use crate::find_reward_pool_spl_program_address;
+use crate::instructions::ReceiptAccounts;
+use crate::state::{DistributionMode, Mining, RewardPool, RewardsRoot};
+use everlend_utils::{
+    assert_account_key, assert_non_zero_amount, find_program_address, AccountLoader, EverlendError,
+};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+/// Instruction context
+pub struct EmergencyWithdrawContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    reward_pool_spl: &'a AccountInfo<'b>,
+    reward_pool_authority: &'a AccountInfo<'b>,
+    mining: &'a AccountInfo<'b>,
+    user_token_account: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    receipt_accounts: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> EmergencyWithdrawContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<EmergencyWithdrawContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool_spl = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+        let reward_pool_authority = AccountLoader::next_uninitialized(account_info_iter)?;
+        let mining = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let user_token_account =
+            AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+        let user = AccountLoader::next_signer(account_info_iter)?;
+
+        let _token_program = AccountLoader::next_with_key(account_info_iter, &spl_token::id())?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+
+        let receipt_accounts = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(EmergencyWithdrawContext {
+            rewards_root,
+            reward_pool,
+            reward_pool_spl,
+            reward_pool_authority,
+            mining,
+            user_token_account,
+            user,
+            receipt_accounts,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        let mut mining = Mining::unpack(&self.mining.data.borrow())?;
+
+        {
+            assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            if !reward_pool.is_paused(&rewards_root) {
+                return Err(EverlendError::NotPaused.into());
+            }
+            reward_pool.assert_not_managed()?;
+        }
+
+        {
+            assert_account_key(self.user, &mining.owner)?;
+            assert_account_key(self.reward_pool, &mining.reward_pool)?;
+
+            let mining_pubkey = Pubkey::create_program_address(
+                &[
+                    b"mining".as_ref(),
+                    self.user.key.as_ref(),
+                    self.reward_pool.key.as_ref(),
+                    &[mining.bump],
+                ],
+                program_id,
+            )?;
+            assert_account_key(self.mining, &mining_pubkey)?;
+
+            let (spl_pubkey, _) = find_reward_pool_spl_program_address(
+                program_id,
+                self.reward_pool.key,
+                &reward_pool.liquidity_mint,
+            );
+            assert_account_key(self.reward_pool_spl, &spl_pubkey)?;
+        }
+
+        // reward logic is skipped, unclaimed rewards are lost with the closed account
+        let receipts = ReceiptAccounts::load(&reward_pool, self.receipt_accounts)?;
+        let amount = match &receipts {
+            // principal of the pool with receipt mint is held by receipts
+            Some(receipts) => {
+                let balance = receipts.balance(program_id, self.mining.key, self.user.key)?;
+
+                // settle the mining with its receipt balance before the exit
+                reward_pool.total_amount = reward_pool
+                    .total_amount
+                    .checked_sub(mining.amount)
+                    .ok_or(EverlendError::MathOverflow)?
+                    .checked_add(balance)
+                    .ok_or(EverlendError::MathOverflow)?;
+                mining.amount = balance;
+
+                balance
+            }
+            None => mining
+                .amount
+                .checked_add(mining.unbonding_amount)
+                .ok_or(EverlendError::MathOverflow)?,
+        };
+        assert_non_zero_amount(amount)?;
+
+        reward_pool.total_amount = reward_pool
+            .total_amount
+            .checked_sub(mining.amount)
+            .ok_or(EverlendError::MathOverflow)?;
+        reward_pool.total_weighted_amount = reward_pool
+            .total_weighted_amount
+            .checked_sub(mining.weighted_amount)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        // rewards of the first layout are forfeited as well
+        reward_pool.migrate_mining(&mut mining)?;
+
+        // forfeited rewards are returned to the vault budgets
+        for vault in reward_pool.vaults.iter_mut() {
+            let reward_index = match mining
+                .indexes
+                .iter()
+                .find(|i| i.reward_mint == vault.reward_mint && i.vault_id == vault.id)
+            {
+                Some(reward_index) => reward_index,
+                None => continue,
+            };
+
+            // proportional share since the last refresh is already accrued by the vault index
+            let pending_rewards = match vault.distribution_mode {
+                DistributionMode::FixedRatio => 0,
+                DistributionMode::Proportional => vault.proportional_rewards(
+                    mining.weighted_amount,
+                    reward_index.index_with_precision,
+                )?,
+            };
+            let forfeited_rewards = reward_index
+                .rewards
+                .checked_add(pending_rewards)
+                .ok_or(EverlendError::MathOverflow)?;
+
+            if forfeited_rewards > 0 {
+                msg!(
+                    "Forfeited rewards: {} of {}",
+                    forfeited_rewards,
+                    vault.reward_mint
+                );
+                vault.total_accrued = vault.total_accrued.saturating_sub(forfeited_rewards);
+            }
+        }
+
+        msg!("Emergency withdraw: {}", amount);
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+
+        if let Some(receipts) = &receipts {
+            receipts.burn(self.user, amount)?;
+        }
+
+        {
+            let (reward_pool_authority, bump_seed) =
+                find_program_address(program_id, self.reward_pool.key);
+            assert_account_key(self.reward_pool_authority, &reward_pool_authority)?;
+            let signers_seeds = &[self.reward_pool.key.as_ref(), &[bump_seed]];
+
+            everlend_utils::cpi::spl_token::transfer(
+                self.reward_pool_spl.clone(),
+                self.user_token_account.clone(),
+                self.reward_pool_authority.clone(),
+                amount,
+                &[signers_seeds],
+            )?;
+        }
+
+        // close mining account
+        everlend_utils::cpi::system::close_account(self.mining, self.user)?;
+
+        Ok(())
+    }
+}
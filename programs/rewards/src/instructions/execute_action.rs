@@ -0,0 +1,210 @@
+use crate::instructions::sweep_and_close_vault;
+use crate::state::{AdminAction, PendingAction, RewardPool, RewardsRoot, VaultCheckpoint};
+use everlend_utils::{assert_account_key, AccountLoader, EverlendError};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::{clock, Sysvar};
+
+/// Instruction context
+pub struct ExecuteActionContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    pending_action: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+    reward_pool: Option<&'a AccountInfo<'b>>,
+    vault: Option<(&'a AccountInfo<'b>, &'a AccountInfo<'b>)>,
+}
+
+impl<'a, 'b> ExecuteActionContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<ExecuteActionContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let pending_action = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let payer = AccountLoader::next_unchecked(account_info_iter)?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+
+        // pool actions
+        let reward_pool = if AccountLoader::has_more(account_info_iter) {
+            Some(AccountLoader::next_with_owner(
+                account_info_iter,
+                program_id,
+            )?)
+        } else {
+            None
+        };
+
+        // vault removal
+        let vault = if AccountLoader::has_more(account_info_iter) {
+            let vault = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+            let destination = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+            let _token_program = AccountLoader::next_with_key(account_info_iter, &spl_token::id())?;
+
+            Some((vault, destination))
+        } else {
+            None
+        };
+
+        Ok(ExecuteActionContext {
+            rewards_root,
+            pending_action,
+            payer,
+            clock,
+            reward_pool,
+            vault,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let pending_action = PendingAction::unpack(&self.pending_action.data.borrow())?;
+        assert_account_key(self.rewards_root, &pending_action.rewards_root)?;
+        assert_account_key(self.payer, &pending_action.payer)?;
+
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+        if timestamp < pending_action.executable_at {
+            msg!(
+                "Action #{} is executable at {}",
+                pending_action.id,
+                pending_action.executable_at
+            );
+            return Err(EverlendError::ActionNotReady.into());
+        }
+
+        let mut rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+
+        let mut reward_pool = match pending_action.action.reward_pool() {
+            Some(reward_pool_pubkey) => {
+                let reward_pool_info =
+                    self.reward_pool.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                assert_account_key(reward_pool_info, reward_pool_pubkey)?;
+
+                let reward_pool = RewardPool::unpack(&reward_pool_info.data.borrow())?;
+                assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+
+                Some((reward_pool_info, reward_pool))
+            }
+            None => None,
+        };
+
+        msg!("Executing action #{}", pending_action.id);
+
+        match (pending_action.action, reward_pool.as_mut()) {
+            (
+                AdminAction::UpdateVault {
+                    reward_mint,
+                    ratio_base,
+                    ratio_quote,
+                    reward_period_sec,
+                    distribution_starts_at,
+                    distribution_ends_at,
+                    reward_max_amount_per_period,
+                    ..
+                },
+                Some((_, reward_pool)),
+            ) => reward_pool.update_vault(
+                reward_mint,
+                VaultCheckpoint {
+                    updated_at: timestamp,
+                    ratio_base,
+                    ratio_quote,
+                    reward_period_sec,
+                    distribution_starts_at,
+                    distribution_ends_at,
+                    reward_max_amount_per_period,
+                },
+            )?,
+            (
+                AdminAction::UpdatePool {
+                    lock_time_sec,
+                    unbonding_period_sec,
+                    early_exit_penalty_bps,
+                    penalty_destination,
+                    max_vaults,
+                    ..
+                },
+                Some((_, reward_pool)),
+            ) => reward_pool.update_params(
+                lock_time_sec,
+                unbonding_period_sec,
+                early_exit_penalty_bps,
+                penalty_destination,
+                max_vaults,
+            )?,
+            (
+                AdminAction::RemoveVault {
+                    reward_mint,
+                    destination,
+                    ..
+                },
+                Some((reward_pool_info, reward_pool)),
+            ) => {
+                let (vault, destination_info) =
+                    self.vault.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                assert_account_key(destination_info, &destination)?;
+
+                sweep_and_close_vault(
+                    program_id,
+                    reward_pool_info,
+                    reward_pool,
+                    &reward_mint,
+                    vault,
+                    destination_info,
+                    self.payer,
+                    timestamp,
+                )?
+            }
+            (
+                AdminAction::SunsetVault {
+                    reward_mint,
+                    claim_period_sec,
+                    ..
+                },
+                Some((_, reward_pool)),
+            ) => reward_pool.sunset_vault(reward_mint, timestamp, claim_period_sec)?,
+            (
+                AdminAction::SetDepositAuthority {
+                    deposit_authority, ..
+                },
+                Some((_, reward_pool)),
+            ) => reward_pool.set_deposit_authority(deposit_authority)?,
+            (AdminAction::SetAuthority { new_authority }, None) => {
+                msg!(
+                    "Root authority {} proposed {}",
+                    rewards_root.authority,
+                    new_authority
+                );
+
+                rewards_root.pending_authority = Some(new_authority);
+            }
+            (AdminAction::SetTimelock { timelock_sec }, None) => {
+                msg!(
+                    "Root timelock changed from {} to {}",
+                    rewards_root.timelock_sec,
+                    timelock_sec
+                );
+
+                rewards_root.timelock_sec = timelock_sec;
+            }
+            (AdminAction::SetTreasury { treasury }, None) => rewards_root.set_treasury(treasury),
+            (AdminAction::SetRole { role, account }, None) => rewards_root.set_role(role, account),
+            _ => return Err(ProgramError::InvalidArgument),
+        }
+
+        if let Some((reward_pool_info, reward_pool)) = reward_pool {
+            reward_pool.save(reward_pool_info, self.payer)?;
+        }
+        RewardsRoot::pack(rewards_root, *self.rewards_root.data.borrow_mut())?;
+
+        everlend_utils::cpi::system::close_account(self.pending_action, self.payer)
+    }
+}
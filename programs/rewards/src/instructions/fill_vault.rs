@@ -1,18 +1,23 @@
-use crate::state::RewardPool;
-use everlend_utils::{assert_account_key, AccountLoader};
+use crate::state::{RewardPool, RewardsRoot, Role};
+use everlend_utils::{assert_account_key, AccountLoader, EverlendError};
 use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::{clock, Sysvar};
 
 /// Instruction context
 pub struct FillVaultContext<'a, 'b> {
+    rewards_root: Option<&'a AccountInfo<'b>>,
     reward_pool: &'a AccountInfo<'b>,
     reward_mint: &'a AccountInfo<'b>,
     vault: &'a AccountInfo<'b>,
     source_token_account: &'a AccountInfo<'b>,
     authority: &'a AccountInfo<'b>,
+    clock: Option<&'a AccountInfo<'b>>,
 }
 
 impl<'a, 'b> FillVaultContext<'a, 'b> {
@@ -20,9 +25,33 @@ impl<'a, 'b> FillVaultContext<'a, 'b> {
     pub fn new(
         program_id: &Pubkey,
         accounts: &'a [AccountInfo<'b>],
+    ) -> Result<FillVaultContext<'a, 'b>, ProgramError> {
+        Self::load(program_id, accounts, true)
+    }
+
+    /// New instruction context of the legacy accounts without the rewards root and clock
+    pub fn new_legacy(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<FillVaultContext<'a, 'b>, ProgramError> {
+        Self::load(program_id, accounts, false)
+    }
+
+    fn load(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+        with_root: bool,
     ) -> Result<FillVaultContext<'a, 'b>, ProgramError> {
         let account_info_iter = &mut accounts.iter().enumerate();
 
+        let rewards_root = if with_root {
+            Some(AccountLoader::next_with_owner(
+                account_info_iter,
+                program_id,
+            )?)
+        } else {
+            None
+        };
         let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
         let reward_mint = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
         let vault = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
@@ -30,19 +59,57 @@ impl<'a, 'b> FillVaultContext<'a, 'b> {
             AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
         let authority = AccountLoader::next_signer(account_info_iter)?;
         let _token_program = AccountLoader::next_with_key(account_info_iter, &spl_token::id())?;
+        let clock = if with_root {
+            Some(AccountLoader::next_with_key(
+                account_info_iter,
+                &clock::id(),
+            )?)
+        } else {
+            None
+        };
 
         Ok(FillVaultContext {
+            rewards_root,
             reward_pool,
             reward_mint,
             vault,
             source_token_account,
             authority,
+            clock,
         })
     }
 
     /// Process instruction
     pub fn process(&self, program_id: &Pubkey, amount: u64) -> ProgramResult {
-        let reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        let timestamp = match self.clock {
+            Some(clock) => Clock::from_account_info(clock)?,
+            None => Clock::get()?,
+        }
+        .unix_timestamp;
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+
+        {
+            let rewards_root = match self.rewards_root {
+                Some(rewards_root) => {
+                    assert_account_key(rewards_root, &reward_pool.rewards_root)?;
+                    let rewards_root = RewardsRoot::unpack(&rewards_root.data.borrow())?;
+                    reward_pool.assert_not_paused(&rewards_root)?;
+                    Some(rewards_root)
+                }
+                None => {
+                    reward_pool.assert_pool_not_paused()?;
+                    None
+                }
+            };
+
+            // root funders may fund every pool of the root
+            let is_root_funder = rewards_root
+                .map(|r| r.has_role(Role::Funder, self.authority.key))
+                .unwrap_or(false);
+            if !reward_pool.funders.contains(self.authority.key) && !is_root_funder {
+                return Err(EverlendError::UnauthorizedFunder.into());
+            }
+        }
 
         {
             let vault = reward_pool
@@ -64,6 +131,34 @@ impl<'a, 'b> FillVaultContext<'a, 'b> {
             )?
         }
 
+        // distribute the periods passed before funding within the old budget
+        reward_pool.refresh_vaults(timestamp as u64)?;
+
+        {
+            let vault = reward_pool
+                .vaults
+                .iter_mut()
+                .find(|v| &v.reward_mint == self.reward_mint.key)
+                .ok_or(ProgramError::InvalidArgument)?;
+
+            if vault.is_ended() {
+                return Err(EverlendError::RewardVaultEnded.into());
+            }
+
+            vault.total_funded = vault
+                .total_funded
+                .checked_add(amount)
+                .ok_or(EverlendError::MathOverflow)?;
+
+            msg!(
+                "Vault {} filled by {} with {}. Total funded: {}",
+                vault.reward_mint,
+                self.authority.key,
+                amount,
+                vault.total_funded
+            );
+        }
+
         everlend_utils::cpi::spl_token::transfer(
             self.source_token_account.clone(),
             self.vault.clone(),
@@ -72,6 +167,8 @@ impl<'a, 'b> FillVaultContext<'a, 'b> {
             &[],
         )?;
 
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+
         Ok(())
     }
 }
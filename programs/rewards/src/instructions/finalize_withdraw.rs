@@ -0,0 +1,120 @@
+use crate::find_reward_pool_spl_program_address;
+use crate::state::{Mining, RewardPool};
+use everlend_utils::{
+    assert_account_key, assert_non_zero_amount, find_program_address, AccountLoader, EverlendError,
+};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program::sysvar::{clock, Sysvar};
+
+/// Instruction context
+pub struct FinalizeWithdrawContext<'a, 'b> {
+    reward_pool: &'a AccountInfo<'b>,
+    reward_pool_spl: &'a AccountInfo<'b>,
+    reward_pool_authority: &'a AccountInfo<'b>,
+    mining: &'a AccountInfo<'b>,
+    user_token_account: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> FinalizeWithdrawContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<FinalizeWithdrawContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool_spl = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+        let reward_pool_authority = AccountLoader::next_uninitialized(account_info_iter)?;
+        let mining = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let user_token_account =
+            AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+        let user = AccountLoader::next_signer(account_info_iter)?;
+
+        let _token_program = AccountLoader::next_with_key(account_info_iter, &spl_token::id())?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+
+        Ok(FinalizeWithdrawContext {
+            reward_pool,
+            reward_pool_spl,
+            reward_pool_authority,
+            mining,
+            user_token_account,
+            user,
+            clock,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        let mut mining = Mining::unpack(&self.mining.data.borrow())?;
+
+        {
+            assert_account_key(self.user, &mining.owner)?;
+            assert_account_key(self.reward_pool, &mining.reward_pool)?;
+
+            let mining_pubkey = Pubkey::create_program_address(
+                &[
+                    b"mining".as_ref(),
+                    self.user.key.as_ref(),
+                    self.reward_pool.key.as_ref(),
+                    &[mining.bump],
+                ],
+                program_id,
+            )?;
+            assert_account_key(self.mining, &mining_pubkey)?;
+
+            let (spl_pubkey, _) = find_reward_pool_spl_program_address(
+                program_id,
+                self.reward_pool.key,
+                &reward_pool.liquidity_mint,
+            );
+            assert_account_key(self.reward_pool_spl, &spl_pubkey)?;
+        }
+
+        let amount = mining.unbonding_amount;
+        assert_non_zero_amount(amount)?;
+
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+        if !mining.is_unbonded(reward_pool.unbonding_period_sec, timestamp) {
+            return Err(EverlendError::UnbondingStillActive.into());
+        }
+
+        mining.unbonding_amount = 0;
+        mining.unbonding_started_at = 0;
+
+        {
+            let (reward_pool_authority, bump_seed) =
+                find_program_address(program_id, self.reward_pool.key);
+            assert_account_key(self.reward_pool_authority, &reward_pool_authority)?;
+            let signers_seeds = &[self.reward_pool.key.as_ref(), &[bump_seed]];
+
+            everlend_utils::cpi::spl_token::transfer(
+                self.reward_pool_spl.clone(),
+                self.user_token_account.clone(),
+                self.reward_pool_authority.clone(),
+                amount,
+                &[signers_seeds],
+            )?;
+        }
+
+        if mining.is_empty() {
+            // close mining account
+            everlend_utils::cpi::system::close_account(self.mining, self.user)?;
+        } else {
+            mining.save(self.mining, self.user)?;
+        }
+
+        Ok(())
+    }
+}
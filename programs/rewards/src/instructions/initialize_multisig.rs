@@ -0,0 +1,70 @@
+use crate::state::Multisig;
+use everlend_utils::{assert_signer, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use solana_program::sysvar::{Sysvar, SysvarId};
+
+/// Instruction context
+pub struct InitializeMultisigContext<'a, 'b> {
+    multisig: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> InitializeMultisigContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        _program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<InitializeMultisigContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let multisig = AccountLoader::next_uninitialized(account_info_iter)?;
+        let payer = AccountLoader::next_signer(account_info_iter)?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
+
+        Ok(InitializeMultisigContext {
+            multisig,
+            payer,
+            rent,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(
+        &self,
+        program_id: &Pubkey,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> ProgramResult {
+        assert_signer(self.multisig)?;
+
+        let multisig = Multisig::init(signers, threshold)?;
+
+        everlend_utils::cpi::system::create_account::<Multisig>(
+            program_id,
+            self.payer.clone(),
+            self.multisig.clone(),
+            &[],
+            &Rent::from_account_info(self.rent)?,
+        )?;
+
+        msg!(
+            "Multisig {} of {} signers created",
+            multisig.threshold,
+            multisig.signers.len()
+        );
+
+        Multisig::pack(multisig, *self.multisig.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
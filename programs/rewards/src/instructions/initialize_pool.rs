@@ -20,6 +20,7 @@ pub struct InitializePoolContext<'a, 'b> {
     liquidity_mint: &'a AccountInfo<'b>,
     payer: &'a AccountInfo<'b>,
     rent: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
 }
 
 impl<'a, 'b> InitializePoolContext<'a, 'b> {
@@ -41,6 +42,8 @@ impl<'a, 'b> InitializePoolContext<'a, 'b> {
             AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
         let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
 
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
         Ok(InitializePoolContext {
             rewards_root,
             reward_pool,
@@ -49,6 +52,7 @@ impl<'a, 'b> InitializePoolContext<'a, 'b> {
             liquidity_mint,
             payer,
             rent,
+            co_signers,
         })
     }
 
@@ -56,7 +60,7 @@ impl<'a, 'b> InitializePoolContext<'a, 'b> {
     pub fn process(&self, program_id: &Pubkey, lock_time_sec: u64) -> ProgramResult {
         {
             let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
-            assert_account_key(self.payer, &rewards_root.authority)?;
+            rewards_root.assert_authority(program_id, self.payer, self.co_signers)?;
         }
 
         self.create_spl_acc(program_id)?;
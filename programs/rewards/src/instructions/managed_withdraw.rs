@@ -0,0 +1,90 @@
+use crate::state::{Mining, RewardPool};
+use everlend_utils::{assert_account_key, assert_non_zero_amount, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program::sysvar::{clock, Sysvar};
+
+/// Instruction context
+pub struct ManagedWithdrawContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    mining: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    deposit_authority: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> ManagedWithdrawContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<ManagedWithdrawContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let mining = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let user = AccountLoader::next_unchecked(account_info_iter)?;
+        let deposit_authority = AccountLoader::next_signer(account_info_iter)?;
+        let payer = AccountLoader::next_signer(account_info_iter)?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+
+        Ok(ManagedWithdrawContext {
+            rewards_root,
+            reward_pool,
+            mining,
+            user,
+            deposit_authority,
+            payer,
+            clock,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey, amount: u64) -> ProgramResult {
+        assert_non_zero_amount(amount)?;
+
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        let mut mining = Mining::unpack(&self.mining.data.borrow())?;
+
+        // not paused: the deposit authority program must always be able to release its balances
+        assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+        reward_pool.assert_deposit_authority(self.deposit_authority.key)?;
+
+        {
+            let mining_pubkey = Pubkey::create_program_address(
+                &[
+                    b"mining".as_ref(),
+                    self.user.key.as_ref(),
+                    self.reward_pool.key.as_ref(),
+                    &[mining.bump],
+                ],
+                program_id,
+            )?;
+            assert_account_key(self.mining, &mining_pubkey)?;
+            assert_account_key(self.reward_pool, &mining.reward_pool)?;
+            assert_account_key(self.user, &mining.owner)?;
+        }
+
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+
+        // locks are kept by the deposit authority program
+        reward_pool.refresh_mining(&mut mining, timestamp)?;
+        mining.unlock(amount, reward_pool.lock_time_sec, timestamp);
+        reward_pool.withdraw(&mut mining, amount, timestamp)?;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        mining.save(self.mining, self.payer)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use crate::state::{AccountType, Mining, RewardPool};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+/// Instruction context
+pub struct MigrateMiningContext<'a, 'b> {
+    reward_pool: &'a AccountInfo<'b>,
+    mining: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> MigrateMiningContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<MigrateMiningContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let mining = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let payer = AccountLoader::next_signer(account_info_iter)?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+
+        Ok(MigrateMiningContext {
+            reward_pool,
+            mining,
+            payer,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        let mut mining = Mining::unpack(&self.mining.data.borrow())?;
+
+        assert_account_key(self.reward_pool, &mining.reward_pool)?;
+        if mining.account_type != AccountType::MiningV1 {
+            msg!("Mining has the current layout");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let timestamp = Clock::get()?.unix_timestamp as u64;
+        reward_pool.refresh_mining(&mut mining, timestamp)?;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        mining.save(self.mining, self.payer)?;
+
+        Ok(())
+    }
+}
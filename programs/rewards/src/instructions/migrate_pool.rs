@@ -1,16 +1,18 @@
-use everlend_utils::cpi::system::realloc_with_rent;
-use everlend_utils::{assert_account_key, AccountLoader};
+use everlend_utils::{assert_account_key, assert_owned_by, AccountLoader};
 use solana_program::account_info::AccountInfo;
-use solana_program::entrypoint_deprecated::ProgramResult;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 use solana_program::system_program;
 use solana_program::sysvar::{Sysvar, SysvarId};
+use spl_token::state::Account;
 
-use crate::find_reward_pool_program_address;
-use crate::state::{RewardPool, RewardsRoot};
+use crate::state::{AccountType, RewardPool, RewardsRoot};
+use crate::{find_reward_pool_program_address, find_vault_program_address};
 
 /// Instruction context
 pub struct MigratePoolContext<'a, 'b> {
@@ -18,7 +20,7 @@ pub struct MigratePoolContext<'a, 'b> {
     reward_pool: &'a AccountInfo<'b>,
     liquidity_mint: &'a AccountInfo<'b>,
     payer: &'a AccountInfo<'b>,
-    rent: &'a AccountInfo<'b>,
+    remaining_accounts: &'a [AccountInfo<'b>],
 }
 
 impl<'a, 'b> MigratePoolContext<'a, 'b> {
@@ -35,41 +37,86 @@ impl<'a, 'b> MigratePoolContext<'a, 'b> {
         let payer = AccountLoader::next_signer(account_info_iter)?;
         let _system_program =
             AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
-        let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
+        let _rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
+
+        let remaining_accounts = AccountLoader::remaining(accounts, account_info_iter);
 
         Ok(MigratePoolContext {
             rewards_root,
             reward_pool,
             liquidity_mint,
             payer,
-            rent,
+            remaining_accounts,
         })
     }
 
     /// Process instruction
-    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
-        let rent = Rent::from_account_info(self.rent)?;
-
-        let deprecated_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
-        let reward_pool = RewardPool::migrate(&deprecated_pool);
+    pub fn process(&self, program_id: &Pubkey, liabilities: Option<Vec<u64>>) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack_unchecked(&self.reward_pool.data.borrow())?;
+        if reward_pool.account_type != AccountType::RewardPoolV1 {
+            msg!("Reward pool has the current layout");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
 
         let (reward_pool_pubkey, _) = find_reward_pool_program_address(
             program_id,
             self.rewards_root.key,
             self.liquidity_mint.key,
         );
+        assert_account_key(self.reward_pool, &reward_pool_pubkey)?;
+        assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+        assert_account_key(self.liquidity_mint, &reward_pool.liquidity_mint)?;
+
+        // vaults of the pool go first, co-signers follow them
+        if self.remaining_accounts.len() < reward_pool.vaults.len() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (vaults, co_signers) = self.remaining_accounts.split_at(reward_pool.vaults.len());
 
         {
             let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
-            assert_account_key(self.payer, &rewards_root.authority)?;
-            assert_account_key(self.reward_pool, &reward_pool_pubkey)?;
-            assert_account_key(self.rewards_root, &deprecated_pool.rewards_root)?;
-            assert_account_key(self.liquidity_mint, &deprecated_pool.liquidity_mint)?;
+            rewards_root.assert_authority(program_id, self.payer, co_signers)?;
+        }
+
+        // without the amounts owed to miners the budget of vaults can't be known
+        let liabilities = liabilities.ok_or_else(|| {
+            msg!("Liabilities of the previous layout are required");
+            ProgramError::InvalidArgument
+        })?;
+        if liabilities.len() != reward_pool.vaults.len() {
+            msg!("Liabilities don't match vaults of the pool");
+            return Err(ProgramError::InvalidArgument);
         }
 
-        realloc_with_rent(self.reward_pool, self.payer, &rent, RewardPool::LEN)?;
+        let timestamp = Clock::get()?.unix_timestamp as u64;
+
+        for (i, (vault, vault_info)) in reward_pool.vaults.iter_mut().zip(vaults).enumerate() {
+            let (vault_pubkey, _) =
+                find_vault_program_address(program_id, self.reward_pool.key, &vault.reward_mint);
+            assert_account_key(vault_info, &vault_pubkey)?;
+            assert_owned_by(vault_info, &spl_token::id())?;
+
+            // rewards left in the vault make up its budget
+            let balance = Account::unpack(&vault_info.data.borrow())?.amount;
+
+            // unclaimed rewards of the previous layout stay owed to miners and are
+            // reserved for them until their minings are converted
+            let accrued = liabilities[i];
+            if accrued > balance {
+                msg!("Liabilities exceed the vault balance");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            vault.total_funded = balance;
+            vault.total_accrued = accrued;
+            vault.legacy_reserve = accrued;
+            // indexes accumulate rewards since the migration
+            vault.index_updated_at = timestamp;
+        }
 
-        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        reward_pool.account_type = AccountType::RewardPoolV2;
+        reward_pool.migrated_at = timestamp;
+        reward_pool.save(self.reward_pool, self.payer)?;
 
         Ok(())
     }
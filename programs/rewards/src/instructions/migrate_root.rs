@@ -0,0 +1,70 @@
+use everlend_utils::cpi::system::realloc_with_rent;
+use everlend_utils::AccountLoader;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use solana_program::sysvar::{Sysvar, SysvarId};
+
+use crate::state::{AccountType, RewardsRoot};
+
+/// Instruction context
+pub struct MigrateRootContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> MigrateRootContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<MigrateRootContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let authority = AccountLoader::next_signer(account_info_iter)?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
+
+        Ok(MigrateRootContext {
+            rewards_root,
+            authority,
+            rent,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let rent = Rent::from_account_info(self.rent)?;
+
+        let mut rewards_root = RewardsRoot::unpack_unchecked(&self.rewards_root.data.borrow())?;
+        if rewards_root.account_type != AccountType::RewardsRootV1 {
+            msg!("Rewards root has the current layout");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        // there were no multisig authorities
+        rewards_root.assert_authority(program_id, self.authority, &[])?;
+
+        rewards_root.account_type = AccountType::RewardsRootV2;
+
+        realloc_with_rent(
+            self.rewards_root,
+            self.authority,
+            Some(self.authority),
+            &rent,
+            RewardsRoot::LEN,
+        )?;
+
+        RewardsRoot::pack(rewards_root, *self.rewards_root.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
@@ -1,19 +1,79 @@
 //! Program instructions
 
+mod accept_root_authority;
+mod add_funder;
 mod add_vault;
+mod cancel_action;
+mod cancel_withdraw;
 mod claim;
+mod claim_all;
 mod deposit_mining;
+mod emergency_withdraw;
+mod execute_action;
 mod fill_vault;
+mod finalize_withdraw;
+mod initialize_multisig;
 mod initialize_pool;
+mod initialize_receipt_mint;
 mod initialize_root;
+mod managed_deposit;
+mod managed_withdraw;
+mod migrate_mining;
 mod migrate_pool;
+mod migrate_root;
+mod propose_action;
+mod propose_root_authority;
+mod remove_funder;
+mod remove_vault;
+mod request_withdraw;
+mod set_deposit_authority;
+mod set_pool_paused;
+mod set_role;
+mod set_root_paused;
+mod set_treasury;
+mod settle_receipt;
+mod sunset_vault;
+mod sync_root_paused;
+mod transfer_mining;
+mod update_pool;
+mod update_vault;
 mod withdraw_mining;
 
+pub use accept_root_authority::*;
+pub use add_funder::*;
 pub use add_vault::*;
+pub use cancel_action::*;
+pub use cancel_withdraw::*;
 pub use claim::*;
+pub use claim_all::*;
 pub use deposit_mining::*;
+pub use emergency_withdraw::*;
+pub use execute_action::*;
 pub use fill_vault::*;
+pub use finalize_withdraw::*;
+pub use initialize_multisig::*;
 pub use initialize_pool::*;
+pub use initialize_receipt_mint::*;
 pub use initialize_root::*;
+pub use managed_deposit::*;
+pub use managed_withdraw::*;
+pub use migrate_mining::*;
 pub use migrate_pool::*;
+pub use migrate_root::*;
+pub use propose_action::*;
+pub use propose_root_authority::*;
+pub use remove_funder::*;
+pub use remove_vault::*;
+pub use request_withdraw::*;
+pub use set_deposit_authority::*;
+pub use set_pool_paused::*;
+pub use set_role::*;
+pub use set_root_paused::*;
+pub use set_treasury::*;
+pub use settle_receipt::*;
+pub use sunset_vault::*;
+pub use sync_root_paused::*;
+pub use transfer_mining::*;
+pub use update_pool::*;
+pub use update_vault::*;
 pub use withdraw_mining::*;
@@ -0,0 +1,116 @@
+use crate::find_pending_action_program_address;
+use crate::state::{AdminAction, InitPendingActionParams, PendingAction, RewardsRoot};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use solana_program::sysvar::{clock, Sysvar, SysvarId};
+
+/// Instruction context
+pub struct ProposeActionContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    pending_action: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> ProposeActionContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<ProposeActionContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let pending_action = AccountLoader::next_uninitialized(account_info_iter)?;
+        let authority = AccountLoader::next_signer(account_info_iter)?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+        let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
+
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(ProposeActionContext {
+            rewards_root,
+            pending_action,
+            authority,
+            clock,
+            rent,
+            co_signers,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey, action: AdminAction) -> ProgramResult {
+        let mut rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+
+        match action.role() {
+            Some(role) => {
+                rewards_root.assert_role(program_id, role, self.authority, self.co_signers)?
+            }
+            None => rewards_root.assert_authority(program_id, self.authority, self.co_signers)?,
+        }
+
+        let id = rewards_root.next_action_id;
+        let bump = {
+            let (pending_action_pubkey, bump) =
+                find_pending_action_program_address(program_id, self.rewards_root.key, id);
+            assert_account_key(self.pending_action, &pending_action_pubkey)?;
+            bump
+        };
+
+        let pending_action_seeds = &[
+            "pending_action".as_bytes(),
+            self.rewards_root.key.as_ref(),
+            &id.to_le_bytes(),
+            &[bump],
+        ];
+
+        everlend_utils::cpi::system::create_account::<PendingAction>(
+            program_id,
+            self.authority.clone(),
+            self.pending_action.clone(),
+            &[pending_action_seeds],
+            &Rent::from_account_info(self.rent)?,
+        )?;
+
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+        let executable_at = timestamp
+            .checked_add(rewards_root.timelock_sec)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        msg!(
+            "Action #{} proposed, executable at {}: {:?}",
+            id,
+            executable_at,
+            action
+        );
+
+        let pending_action = PendingAction::init(InitPendingActionParams {
+            rewards_root: *self.rewards_root.key,
+            bump,
+            id,
+            payer: *self.authority.key,
+            proposed_at: timestamp,
+            executable_at,
+            action,
+        });
+
+        rewards_root.next_action_id = id.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+
+        PendingAction::pack(pending_action, *self.pending_action.data.borrow_mut())?;
+        RewardsRoot::pack(rewards_root, *self.rewards_root.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
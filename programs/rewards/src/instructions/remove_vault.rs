@@ -0,0 +1,162 @@
+use crate::state::{RewardPool, RewardsRoot, Role};
+use everlend_utils::{assert_account_key, AccountLoader, EverlendError};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::{clock, Sysvar};
+use spl_token::state::Account;
+
+/// Instruction context
+pub struct RemoveVaultContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    reward_mint: &'a AccountInfo<'b>,
+    vault: &'a AccountInfo<'b>,
+    destination: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> RemoveVaultContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<RemoveVaultContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_mint = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+        let vault = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+        let destination = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+        let authority = AccountLoader::next_signer(account_info_iter)?;
+        let _token_program = AccountLoader::next_with_key(account_info_iter, &spl_token::id())?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(RemoveVaultContext {
+            rewards_root,
+            reward_pool,
+            reward_mint,
+            vault,
+            destination,
+            authority,
+            clock,
+            co_signers,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+
+        {
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            rewards_root.assert_role(
+                program_id,
+                Role::VaultManager,
+                self.authority,
+                self.co_signers,
+            )?;
+            rewards_root.assert_not_timelocked()?;
+        }
+
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+
+        sweep_and_close_vault(
+            program_id,
+            self.reward_pool,
+            &mut reward_pool,
+            self.reward_mint.key,
+            self.vault,
+            self.destination,
+            self.authority,
+            timestamp,
+        )?;
+
+        reward_pool.save(self.reward_pool, self.authority)?;
+
+        Ok(())
+    }
+}
+
+/// Removes the ended vault from the pool, sweeps its leftovers and closes it
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn sweep_and_close_vault<'a, 'b>(
+    program_id: &Pubkey,
+    reward_pool_info: &'a AccountInfo<'b>,
+    reward_pool: &mut RewardPool,
+    reward_mint: &Pubkey,
+    vault_info: &'a AccountInfo<'b>,
+    destination: &'a AccountInfo<'b>,
+    rent_destination: &'a AccountInfo<'b>,
+    timestamp: u64,
+) -> ProgramResult {
+    let reward_pool_seeds = &[
+        b"reward_pool".as_ref(),
+        &reward_pool.rewards_root.to_bytes()[..32],
+        &reward_pool.liquidity_mint.to_bytes()[..32],
+        &[reward_pool.bump],
+    ];
+
+    assert_account_key(
+        reward_pool_info,
+        &Pubkey::create_program_address(reward_pool_seeds, program_id)?,
+    )?;
+
+    let vault = reward_pool.remove_vault(*reward_mint)?;
+
+    {
+        let vault_seeds = &[
+            b"vault".as_ref(),
+            &reward_pool_info.key.to_bytes()[..32],
+            &reward_mint.to_bytes()[..32],
+            &[vault.bump],
+        ];
+
+        assert_account_key(
+            vault_info,
+            &Pubkey::create_program_address(vault_seeds, program_id)?,
+        )?;
+    }
+
+    if !vault.is_ended() || timestamp < vault.claim_deadline {
+        return Err(EverlendError::ClaimDeadlineNotPassed.into());
+    }
+
+    // sweep leftovers
+    let amount = Account::unpack(&vault_info.data.borrow())?.amount;
+    if amount > 0 {
+        everlend_utils::cpi::spl_token::transfer(
+            vault_info.clone(),
+            destination.clone(),
+            reward_pool_info.clone(),
+            amount,
+            &[reward_pool_seeds],
+        )?;
+    }
+
+    everlend_utils::cpi::spl_token::close_account(
+        rent_destination.clone(),
+        vault_info.clone(),
+        reward_pool_info.clone(),
+        &[reward_pool_seeds],
+    )?;
+
+    msg!(
+        "Vault {} removed. Swept {}, unclaimed rewards {}",
+        vault.reward_mint,
+        amount,
+        vault.liabilities()
+    );
+
+    Ok(())
+}
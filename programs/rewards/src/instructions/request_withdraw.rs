@@ -0,0 +1,90 @@
+use crate::state::{Mining, RewardPool, RewardsRoot};
+use everlend_utils::{assert_account_key, assert_non_zero_amount, AccountLoader, EverlendError};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+use solana_program::sysvar::{clock, Sysvar};
+
+/// Instruction context
+pub struct RequestWithdrawContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    mining: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> RequestWithdrawContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<RequestWithdrawContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let mining = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let user = AccountLoader::next_signer(account_info_iter)?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+
+        Ok(RequestWithdrawContext {
+            rewards_root,
+            reward_pool,
+            mining,
+            user,
+            clock,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey, amount: u64) -> ProgramResult {
+        assert_non_zero_amount(amount)?;
+
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        let mut mining = Mining::unpack(&self.mining.data.borrow())?;
+
+        {
+            assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            reward_pool.assert_not_paused(&rewards_root)?;
+            reward_pool.assert_not_managed()?;
+            reward_pool.assert_no_receipts()?;
+        }
+
+        {
+            assert_account_key(self.user, &mining.owner)?;
+            assert_account_key(self.reward_pool, &mining.reward_pool)?;
+
+            let mining_pubkey = Pubkey::create_program_address(
+                &[
+                    b"mining".as_ref(),
+                    self.user.key.as_ref(),
+                    self.reward_pool.key.as_ref(),
+                    &[mining.bump],
+                ],
+                program_id,
+            )?;
+            assert_account_key(self.mining, &mining_pubkey)?;
+        }
+
+        // only unlocked tranches can be requested
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+        if amount > mining.unlocked_amount(reward_pool.lock_time_sec, timestamp) {
+            return Err(EverlendError::LockTimeStillActive.into());
+        }
+
+        reward_pool.request_withdraw(&mut mining, amount, timestamp)?;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        mining.save(self.mining, self.user)?;
+
+        Ok(())
+    }
+}
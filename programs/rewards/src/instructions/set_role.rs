@@ -0,0 +1,51 @@
+use crate::state::{RewardsRoot, Role};
+use everlend_utils::AccountLoader;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Instruction context
+pub struct SetRoleContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    account: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> SetRoleContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<SetRoleContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let authority = AccountLoader::next_signer(account_info_iter)?;
+        let account = AccountLoader::next_unchecked(account_info_iter)?;
+
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(SetRoleContext {
+            rewards_root,
+            authority,
+            account,
+            co_signers,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey, role: Role) -> ProgramResult {
+        let mut rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+        rewards_root.assert_authority(program_id, self.authority, self.co_signers)?;
+        rewards_root.assert_not_timelocked()?;
+
+        rewards_root.set_role(role, *self.account.key);
+
+        RewardsRoot::pack(rewards_root, *self.rewards_root.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use crate::state::{RewardsRoot, Role};
+use everlend_utils::AccountLoader;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Instruction context
+pub struct SetRootPausedContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    pauser: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> SetRootPausedContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<SetRootPausedContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let pauser = AccountLoader::next_signer(account_info_iter)?;
+
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(SetRootPausedContext {
+            rewards_root,
+            pauser,
+            co_signers,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey, paused: bool) -> ProgramResult {
+        let mut rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+        rewards_root.assert_role(program_id, Role::Pauser, self.pauser, self.co_signers)?;
+
+        msg!("Rewards root paused: {}", paused);
+
+        rewards_root.paused = paused;
+
+        RewardsRoot::pack(rewards_root, *self.rewards_root.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
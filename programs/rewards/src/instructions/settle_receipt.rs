@@ -0,0 +1,259 @@
+use crate::state::{Mining, RewardPool, RewardsRoot};
+use crate::{find_mining_program_address, find_receipt_program_address};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use solana_program::sysvar::{clock, Sysvar, SysvarId};
+use spl_token::state::Account;
+
+/// Receipt mint and receipt token account of the mining
+pub(crate) struct ReceiptAccounts<'a, 'b> {
+    receipt_mint: &'a AccountInfo<'b>,
+    receipt_account: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> ReceiptAccounts<'a, 'b> {
+    /// Load receipt accounts of the pool with receipt mint
+    pub fn load(
+        reward_pool: &RewardPool,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<Option<ReceiptAccounts<'a, 'b>>, ProgramError> {
+        let receipt_mint = match reward_pool.receipt_mint {
+            Some(receipt_mint) => receipt_mint,
+            None => return Ok(None),
+        };
+
+        let account_info_iter = &mut accounts.iter().enumerate();
+        let receipt_mint = AccountLoader::next_with_key(account_info_iter, &receipt_mint)?;
+        let receipt_account = AccountLoader::next_optional(account_info_iter, &spl_token::id())?;
+
+        Ok(Some(ReceiptAccounts {
+            receipt_mint,
+            receipt_account,
+        }))
+    }
+
+    /// Create receipt token account of the mining owner if it doesn't exist
+    pub fn check_and_init(
+        &self,
+        program_id: &Pubkey,
+        mining: &Pubkey,
+        owner: &AccountInfo<'b>,
+        payer: &AccountInfo<'b>,
+        rent: &AccountInfo<'b>,
+    ) -> ProgramResult {
+        let (receipt_pubkey, bump) = find_receipt_program_address(program_id, mining);
+        assert_account_key(self.receipt_account, &receipt_pubkey)?;
+
+        if !self.receipt_account.owner.eq(&Pubkey::default()) {
+            return Ok(());
+        }
+
+        let signers_seeds = &[b"receipt".as_ref(), mining.as_ref(), &[bump]];
+
+        everlend_utils::cpi::system::create_account::<Account>(
+            &spl_token::id(),
+            payer.clone(),
+            self.receipt_account.clone(),
+            &[signers_seeds],
+            &Rent::from_account_info(rent)?,
+        )?;
+
+        everlend_utils::cpi::spl_token::initialize_account(
+            self.receipt_account.clone(),
+            self.receipt_mint.clone(),
+            owner.clone(),
+            rent.clone(),
+        )
+    }
+
+    /// Receipt balance of the mining owner.
+    /// Nothing is counted once the account is closed or handed over to another owner
+    pub fn balance(
+        &self,
+        program_id: &Pubkey,
+        mining: &Pubkey,
+        owner: &Pubkey,
+    ) -> Result<u64, ProgramError> {
+        let (receipt_pubkey, _) = find_receipt_program_address(program_id, mining);
+        assert_account_key(self.receipt_account, &receipt_pubkey)?;
+
+        if self.receipt_account.owner.eq(&Pubkey::default()) {
+            return Ok(0);
+        }
+
+        let receipt_account = Account::unpack(&self.receipt_account.data.borrow())?;
+        if receipt_account.owner != *owner {
+            return Ok(0);
+        }
+
+        Ok(receipt_account.amount)
+    }
+
+    /// Mint receipts signed by the reward pool
+    pub fn mint(
+        &self,
+        reward_pool: &AccountInfo<'b>,
+        reward_pool_seeds: &[&[u8]],
+        amount: u64,
+    ) -> ProgramResult {
+        everlend_utils::cpi::spl_token::mint_to(
+            self.receipt_mint.clone(),
+            self.receipt_account.clone(),
+            reward_pool.clone(),
+            amount,
+            &[reward_pool_seeds],
+        )
+    }
+
+    /// Burn receipts of the mining owner
+    pub fn burn(&self, owner: &AccountInfo<'b>, amount: u64) -> ProgramResult {
+        everlend_utils::cpi::spl_token::burn(
+            self.receipt_mint.clone(),
+            self.receipt_account.clone(),
+            owner.clone(),
+            amount,
+            &[],
+        )
+    }
+}
+
+/// Instruction context
+pub struct SettleReceiptContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    mining: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+    receipt_accounts: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> SettleReceiptContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<SettleReceiptContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let mining = AccountLoader::next_optional(account_info_iter, program_id)?;
+        let user = AccountLoader::next_unchecked(account_info_iter)?;
+        let payer = AccountLoader::next_signer(account_info_iter)?;
+        let _token_program = AccountLoader::next_with_key(account_info_iter, &spl_token::id())?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+        let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
+
+        let receipt_accounts = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(SettleReceiptContext {
+            rewards_root,
+            reward_pool,
+            mining,
+            user,
+            payer,
+            clock,
+            rent,
+            receipt_accounts,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        {
+            assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            reward_pool.assert_not_paused(&rewards_root)?;
+        }
+
+        let receipts = ReceiptAccounts::load(&reward_pool, self.receipt_accounts)?
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        let mut mining = self.check_and_init_mining(program_id)?;
+        {
+            let mining_pubkey = Pubkey::create_program_address(
+                &[
+                    b"mining".as_ref(),
+                    self.user.key.as_ref(),
+                    self.reward_pool.key.as_ref(),
+                    &[mining.bump],
+                ],
+                program_id,
+            )?;
+            assert_account_key(self.mining, &mining_pubkey)?;
+            assert_account_key(self.reward_pool, &mining.reward_pool)?;
+            assert_account_key(self.user, &mining.owner)?;
+        }
+
+        receipts.check_and_init(
+            program_id,
+            self.mining.key,
+            self.user,
+            self.payer,
+            self.rent,
+        )?;
+        let balance = receipts.balance(program_id, self.mining.key, self.user.key)?;
+
+        msg!(
+            "Mining amount settled from {} to {}",
+            mining.amount,
+            balance
+        );
+
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+        reward_pool.settle(&mut mining, balance, timestamp)?;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        mining.save(self.mining, self.payer)?;
+
+        Ok(())
+    }
+
+    /// Load mining, creating it at the payer's expense
+    pub fn check_and_init_mining(&self, program_id: &Pubkey) -> Result<Mining, ProgramError> {
+        if !self.mining.owner.eq(&Pubkey::default()) {
+            return Mining::unpack(&self.mining.data.borrow());
+        }
+
+        let bump = {
+            let (pubkey, bump) =
+                find_mining_program_address(program_id, self.user.key, self.reward_pool.key);
+            assert_account_key(self.mining, &pubkey)?;
+            bump
+        };
+
+        let signers_seeds = &[
+            "mining".as_bytes(),
+            &self.user.key.to_bytes(),
+            &self.reward_pool.key.to_bytes(),
+            &[bump],
+        ];
+
+        everlend_utils::cpi::system::create_account::<Mining>(
+            program_id,
+            self.payer.clone(),
+            self.mining.clone(),
+            &[signers_seeds],
+            &Rent::from_account_info(self.rent)?,
+        )?;
+
+        Ok(Mining::initialize(
+            *self.reward_pool.key,
+            bump,
+            *self.user.key,
+        ))
+    }
+}
@@ -0,0 +1,52 @@
+use crate::state::{RewardPool, RewardsRoot};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Instruction context
+pub struct SyncRootPausedContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> SyncRootPausedContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<SyncRootPausedContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+
+        Ok(SyncRootPausedContext {
+            rewards_root,
+            reward_pool,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+
+        let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+
+        msg!(
+            "Reward pool {} root paused: {}",
+            self.reward_pool.key,
+            rewards_root.paused
+        );
+
+        reward_pool.root_paused = rewards_root.paused;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use crate::find_mining_program_address;
+use crate::state::{Mining, RewardPool, RewardsRoot};
+use everlend_utils::{assert_account_key, assert_non_zero_amount, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use solana_program::sysvar::{clock, Sysvar, SysvarId};
+
+/// Instruction context
+pub struct TransferMiningContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    mining: &'a AccountInfo<'b>,
+    destination_mining: &'a AccountInfo<'b>,
+    user: &'a AccountInfo<'b>,
+    destination_user: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+    rent: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> TransferMiningContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<TransferMiningContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let mining = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let destination_mining = AccountLoader::next_unchecked(account_info_iter)?; // unchecked so we can create on the fly
+        let user = AccountLoader::next_signer(account_info_iter)?;
+        let destination_user = AccountLoader::next_signer(account_info_iter)?;
+        let _system_program =
+            AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+        let rent = AccountLoader::next_with_key(account_info_iter, &Rent::id())?;
+
+        Ok(TransferMiningContext {
+            rewards_root,
+            reward_pool,
+            mining,
+            destination_mining,
+            user,
+            destination_user,
+            clock,
+            rent,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(&self, program_id: &Pubkey, amount: u64) -> ProgramResult {
+        assert_non_zero_amount(amount)?;
+
+        if self.user.key == self.destination_user.key {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        {
+            assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            reward_pool.assert_not_paused(&rewards_root)?;
+            // balances of the managed pool are tracked by the deposit authority
+            reward_pool.assert_not_managed()?;
+            // stake of the receipt pool is moved with receipts
+            reward_pool.assert_no_receipts()?;
+        }
+
+        let mut mining = Mining::unpack(&self.mining.data.borrow())?;
+        {
+            let mining_pubkey = Pubkey::create_program_address(
+                &[
+                    b"mining".as_ref(),
+                    self.user.key.as_ref(),
+                    self.reward_pool.key.as_ref(),
+                    &[mining.bump],
+                ],
+                program_id,
+            )?;
+            assert_account_key(self.mining, &mining_pubkey)?;
+            assert_account_key(self.reward_pool, &mining.reward_pool)?;
+            assert_account_key(self.user, &mining.owner)?;
+        }
+
+        let mut destination_mining = self.check_and_init_destination_mining(program_id)?;
+        {
+            let mining_pubkey = Pubkey::create_program_address(
+                &[
+                    b"mining".as_ref(),
+                    self.destination_user.key.as_ref(),
+                    self.reward_pool.key.as_ref(),
+                    &[destination_mining.bump],
+                ],
+                program_id,
+            )?;
+            assert_account_key(self.destination_mining, &mining_pubkey)?;
+            assert_account_key(self.reward_pool, &destination_mining.reward_pool)?;
+            assert_account_key(self.destination_user, &destination_mining.owner)?;
+        }
+
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+        reward_pool.transfer(&mut mining, &mut destination_mining, amount, timestamp)?;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+        destination_mining.save(self.destination_mining, self.user)?;
+
+        if mining.is_empty() {
+            // close mining account
+            everlend_utils::cpi::system::close_account(self.mining, self.user)?;
+        } else {
+            mining.save(self.mining, self.user)?;
+        }
+
+        Ok(())
+    }
+
+    /// Load destination mining, creating it at the user's expense
+    pub fn check_and_init_destination_mining(
+        &self,
+        program_id: &Pubkey,
+    ) -> Result<Mining, ProgramError> {
+        if self.destination_mining.owner.eq(&Pubkey::default()) {
+            let bump = {
+                let (pubkey, bump) = find_mining_program_address(
+                    program_id,
+                    self.destination_user.key,
+                    self.reward_pool.key,
+                );
+                assert_account_key(self.destination_mining, &pubkey)?;
+                bump
+            };
+
+            let signers_seeds = &[
+                "mining".as_bytes(),
+                &self.destination_user.key.to_bytes(),
+                &self.reward_pool.key.to_bytes(),
+                &[bump],
+            ];
+
+            everlend_utils::cpi::system::create_account::<Mining>(
+                program_id,
+                self.user.clone(),
+                self.destination_mining.clone(),
+                &[signers_seeds],
+                &Rent::from_account_info(self.rent)?,
+            )?;
+
+            return Ok(Mining::initialize(
+                *self.reward_pool.key,
+                bump,
+                *self.destination_user.key,
+            ));
+        }
+
+        if self.destination_mining.owner.eq(program_id) {
+            return Mining::unpack(&self.destination_mining.data.borrow());
+        }
+
+        Err(ProgramError::InvalidAccountOwner)
+    }
+}
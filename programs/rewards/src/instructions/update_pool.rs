@@ -0,0 +1,70 @@
+use crate::state::{PenaltyDestination, RewardPool, RewardsRoot};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+
+/// Instruction context
+pub struct UpdatePoolContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> UpdatePoolContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<UpdatePoolContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let authority = AccountLoader::next_signer(account_info_iter)?;
+
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(UpdatePoolContext {
+            rewards_root,
+            reward_pool,
+            authority,
+            co_signers,
+        })
+    }
+
+    /// Process instruction
+    pub fn process(
+        &self,
+        program_id: &Pubkey,
+        lock_time_sec: u64,
+        unbonding_period_sec: u64,
+        early_exit_penalty_bps: u16,
+        penalty_destination: PenaltyDestination,
+        max_vaults: u8,
+    ) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+
+        {
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            rewards_root.assert_authority(program_id, self.authority, self.co_signers)?;
+            rewards_root.assert_not_timelocked()?;
+        }
+
+        reward_pool.update_params(
+            lock_time_sec,
+            unbonding_period_sec,
+            early_exit_penalty_bps,
+            penalty_destination,
+            max_vaults,
+        )?;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use crate::state::{RewardPool, RewardsRoot, Role, VaultCheckpoint};
+use everlend_utils::{assert_account_key, AccountLoader};
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::{clock, Sysvar};
+
+/// Instruction context
+pub struct UpdateVaultContext<'a, 'b> {
+    rewards_root: &'a AccountInfo<'b>,
+    reward_pool: &'a AccountInfo<'b>,
+    reward_mint: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+    co_signers: &'a [AccountInfo<'b>],
+}
+
+impl<'a, 'b> UpdateVaultContext<'a, 'b> {
+    /// New instruction context
+    pub fn new(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<UpdateVaultContext<'a, 'b>, ProgramError> {
+        let account_info_iter = &mut accounts.iter().enumerate();
+
+        let rewards_root = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
+        let reward_mint = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
+        let authority = AccountLoader::next_signer(account_info_iter)?;
+        let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
+
+        let co_signers = AccountLoader::remaining(accounts, account_info_iter);
+
+        Ok(UpdateVaultContext {
+            rewards_root,
+            reward_pool,
+            reward_mint,
+            authority,
+            clock,
+            co_signers,
+        })
+    }
+
+    /// Process instruction
+    #[allow(clippy::too_many_arguments)]
+    pub fn process(
+        &self,
+        program_id: &Pubkey,
+        ratio_base: u64,
+        ratio_quote: u64,
+        reward_period_sec: u32,
+        distribution_starts_at: u64,
+        distribution_ends_at: Option<u64>,
+        reward_max_amount_per_period: u64,
+    ) -> ProgramResult {
+        let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
+        assert_account_key(self.rewards_root, &reward_pool.rewards_root)?;
+
+        {
+            let rewards_root = RewardsRoot::unpack(&self.rewards_root.data.borrow())?;
+            rewards_root.assert_role(
+                program_id,
+                Role::VaultManager,
+                self.authority,
+                self.co_signers,
+            )?;
+            rewards_root.assert_not_timelocked()?;
+        }
+
+        let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
+
+        reward_pool.update_vault(
+            *self.reward_mint.key,
+            VaultCheckpoint {
+                updated_at: timestamp,
+                ratio_base,
+                ratio_quote,
+                reward_period_sec,
+                distribution_starts_at,
+                distribution_ends_at,
+                reward_max_amount_per_period,
+            },
+        )?;
+
+        RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
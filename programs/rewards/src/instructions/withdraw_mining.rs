@@ -1,17 +1,24 @@
 use crate::find_reward_pool_spl_program_address;
-use crate::state::{Mining, RewardPool};
-use everlend_utils::{assert_account_key, find_program_address, AccountLoader, EverlendError};
+use crate::instructions::ReceiptAccounts;
+use crate::state::{Mining, PenaltyDestination, RewardPool, RewardsRoot};
+use everlend_utils::{
+    assert_account_key, assert_non_zero_amount, assert_owned_by, find_program_address,
+    AccountLoader, EverlendError,
+};
 use solana_program::account_info::AccountInfo;
 use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program::system_program;
 use solana_program::sysvar::{clock, Sysvar};
+use spl_token::state::Account;
 
 /// Instruction context
 pub struct WithdrawMiningContext<'a, 'b> {
+    rewards_root: Option<&'a AccountInfo<'b>>,
     reward_pool: &'a AccountInfo<'b>,
     reward_pool_spl: &'a AccountInfo<'b>,
     reward_pool_authority: &'a AccountInfo<'b>,
@@ -20,6 +27,7 @@ pub struct WithdrawMiningContext<'a, 'b> {
     user_token_account: &'a AccountInfo<'b>,
     user: &'a AccountInfo<'b>,
     clock: &'a AccountInfo<'b>,
+    remaining_accounts: &'a [AccountInfo<'b>],
 }
 
 impl<'a, 'b> WithdrawMiningContext<'a, 'b> {
@@ -27,8 +35,32 @@ impl<'a, 'b> WithdrawMiningContext<'a, 'b> {
     pub fn new(
         program_id: &Pubkey,
         accounts: &'a [AccountInfo<'b>],
+    ) -> Result<WithdrawMiningContext<'a, 'b>, ProgramError> {
+        Self::load(program_id, accounts, true)
+    }
+
+    /// New instruction context of the legacy accounts without the rewards root
+    pub fn new_legacy(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> Result<WithdrawMiningContext<'a, 'b>, ProgramError> {
+        Self::load(program_id, accounts, false)
+    }
+
+    fn load(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+        with_root: bool,
     ) -> Result<WithdrawMiningContext<'a, 'b>, ProgramError> {
         let account_info_iter = &mut accounts.iter().enumerate();
+        let rewards_root = if with_root {
+            Some(AccountLoader::next_with_owner(
+                account_info_iter,
+                program_id,
+            )?)
+        } else {
+            None
+        };
         let reward_pool = AccountLoader::next_with_owner(account_info_iter, program_id)?;
         let reward_pool_spl = AccountLoader::next_with_owner(account_info_iter, &spl_token::id())?;
         let reward_pool_authority = AccountLoader::next_uninitialized(account_info_iter)?;
@@ -43,7 +75,11 @@ impl<'a, 'b> WithdrawMiningContext<'a, 'b> {
             AccountLoader::next_with_key(account_info_iter, &system_program::id())?;
         let clock = AccountLoader::next_with_key(account_info_iter, &clock::id())?;
 
+        // treasury token account or receipt accounts of the pool with receipt mint
+        let remaining_accounts = AccountLoader::remaining(accounts, account_info_iter);
+
         Ok(WithdrawMiningContext {
+            rewards_root,
             reward_pool,
             reward_pool_spl,
             reward_pool_authority,
@@ -52,13 +88,31 @@ impl<'a, 'b> WithdrawMiningContext<'a, 'b> {
             user_token_account,
             user,
             clock,
+            remaining_accounts,
         })
     }
 
-    /// Process instruction
-    pub fn process(&self, program_id: &Pubkey) -> ProgramResult {
+    /// Process instruction, withdraws the whole staked amount when no amount is passed
+    pub fn process(
+        &self,
+        program_id: &Pubkey,
+        amount: Option<u64>,
+        forfeit: bool,
+    ) -> ProgramResult {
         let mut reward_pool = RewardPool::unpack(&self.reward_pool.data.borrow())?;
-        let mining = Mining::unpack(&self.mining.data.borrow())?;
+        let mut mining = Mining::unpack(&self.mining.data.borrow())?;
+
+        {
+            match self.rewards_root {
+                Some(rewards_root) => {
+                    assert_account_key(rewards_root, &reward_pool.rewards_root)?;
+                    let rewards_root = RewardsRoot::unpack(&rewards_root.data.borrow())?;
+                    reward_pool.assert_not_paused(&rewards_root)?;
+                }
+                None => reward_pool.assert_pool_not_paused()?,
+            }
+            reward_pool.assert_not_managed()?;
+        }
 
         let reward_pool_seeds = &[
             b"reward_pool".as_ref(),
@@ -99,21 +153,134 @@ impl<'a, 'b> WithdrawMiningContext<'a, 'b> {
             assert_account_key(self.reward_pool_spl, &spl_pubkey)?;
         }
 
-        // check if it's allowed to withdraw
+        if reward_pool.unbonding_period_sec > 0 {
+            return Err(EverlendError::UnbondingRequired.into());
+        }
+
         let timestamp = Clock::from_account_info(self.clock)?.unix_timestamp as u64;
-        if timestamp.saturating_sub(mining.last_deposit_time) < reward_pool.lock_time_sec {
+
+        let receipts = ReceiptAccounts::load(&reward_pool, self.remaining_accounts)?;
+        if let Some(receipts) = &receipts {
+            let balance = receipts.balance(program_id, self.mining.key, self.user.key)?;
+            reward_pool.settle(&mut mining, balance, timestamp)?;
+        }
+
+        let amount = amount.unwrap_or(mining.amount);
+        assert_non_zero_amount(amount)?;
+
+        // locked amount can be withdrawn early only with a penalty
+        reward_pool.refresh_mining(&mut mining, timestamp)?;
+        let locked_amount =
+            amount.saturating_sub(mining.unlocked_amount(reward_pool.lock_time_sec, timestamp));
+        if locked_amount > 0 && reward_pool.early_exit_penalty_bps == 0 {
             return Err(EverlendError::LockTimeStillActive.into());
         }
 
-        reward_pool.withdraw(mining.amount)?;
+        mining.unlock(locked_amount, reward_pool.lock_time_sec, timestamp);
+        let penalty = reward_pool.early_exit_penalty(locked_amount)?;
+
+        reward_pool.withdraw(&mut mining, amount, timestamp)?;
+
+        let mut shared_penalty = 0;
+        if penalty > 0 && reward_pool.penalty_destination == PenaltyDestination::Stakers {
+            shared_penalty = reward_pool.share_penalty(penalty)?;
+        }
+
+        // rewards are lost with the closed account
+        let close_mining = mining.amount == 0 && mining.unbonding_amount == 0;
+        if close_mining {
+            for reward_index in mining.indexes.iter().filter(|i| i.rewards > 0) {
+                if !forfeit {
+                    return Err(EverlendError::UnclaimedRewards.into());
+                }
+
+                msg!(
+                    "Forfeited rewards: {} of {}",
+                    reward_index.rewards,
+                    reward_index.reward_mint
+                );
+
+                // forfeited rewards are returned to the vault budget
+                if let Some(vault) = reward_pool
+                    .vaults
+                    .iter_mut()
+                    .find(|v| v.reward_mint == reward_index.reward_mint)
+                {
+                    vault.total_accrued = vault.total_accrued.saturating_sub(reward_index.rewards);
+                }
+            }
+        }
+
+        if penalty > 0 {
+            msg!("Early exit penalty: {}", penalty);
+
+            // penalty goes to the treasury when there are no stakers to share it with
+            if shared_penalty == 0 {
+                self.treasury_transfer(program_id, &reward_pool, penalty)?;
+            }
+        }
+
         RewardPool::pack(reward_pool, *self.reward_pool.data.borrow_mut())?;
 
-        self.spl_transfer_and_close(program_id, mining.amount)?;
+        if let Some(receipts) = &receipts {
+            receipts.burn(self.user, amount)?;
+        }
+
+        self.spl_transfer(
+            program_id,
+            self.user_token_account,
+            amount
+                .checked_sub(penalty)
+                .ok_or(EverlendError::MathOverflow)?,
+        )?;
+
+        if close_mining {
+            // close mining account
+            everlend_utils::cpi::system::close_account(self.mining, self.user)?;
+        } else {
+            mining.save(self.mining, self.user)?;
+        }
 
         Ok(())
     }
 
-    fn spl_transfer_and_close(&self, program_id: &Pubkey, amount: u64) -> ProgramResult {
+    fn treasury_transfer(
+        &self,
+        program_id: &Pubkey,
+        reward_pool: &RewardPool,
+        amount: u64,
+    ) -> ProgramResult {
+        let treasury_token_account = self
+            .remaining_accounts
+            .first()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        assert_owned_by(treasury_token_account, &spl_token::id())?;
+
+        {
+            // legacy accounts can't pay the penalty to the treasury
+            let rewards_root = self
+                .rewards_root
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let rewards_root = RewardsRoot::unpack(&rewards_root.data.borrow())?;
+            let treasury_token = Account::unpack(&treasury_token_account.data.borrow())?;
+
+            if rewards_root.treasury == Pubkey::default()
+                || treasury_token.owner != rewards_root.treasury
+                || treasury_token.mint != reward_pool.liquidity_mint
+            {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        self.spl_transfer(program_id, treasury_token_account, amount)
+    }
+
+    fn spl_transfer(
+        &self,
+        program_id: &Pubkey,
+        destination: &'a AccountInfo<'b>,
+        amount: u64,
+    ) -> ProgramResult {
         let (reward_pool_authority, bump_seed) =
             find_program_address(program_id, self.reward_pool.key);
         assert_account_key(self.reward_pool_authority, &reward_pool_authority)?;
@@ -122,15 +289,12 @@ impl<'a, 'b> WithdrawMiningContext<'a, 'b> {
         // Transfer token from source to token account
         everlend_utils::cpi::spl_token::transfer(
             self.reward_pool_spl.clone(),
-            self.user_token_account.clone(),
+            destination.clone(),
             self.reward_pool_authority.clone(),
             amount,
             &[signers_seeds],
         )?;
 
-        // close mining account
-        everlend_utils::cpi::system::close_account(self.mining, self.user)?;
-
         Ok(())
     }
 }
@@ -78,3 +78,35 @@ pub fn find_reward_pool_spl_program_address(
         program_id,
     )
 }
+
+/// Generates pending admin action address
+pub fn find_pending_action_program_address(
+    program_id: &Pubkey,
+    rewards_root: &Pubkey,
+    id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            "pending_action".as_bytes(),
+            &rewards_root.to_bytes(),
+            &id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Generates receipt mint address
+pub fn find_receipt_mint_program_address(
+    program_id: &Pubkey,
+    reward_pool: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &["receipt_mint".as_bytes(), &reward_pool.to_bytes()],
+        program_id,
+    )
+}
+
+/// Generates receipt token account address of the mining
+pub fn find_receipt_program_address(program_id: &Pubkey, mining: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&["receipt".as_bytes(), &mining.to_bytes()], program_id)
+}
@@ -2,8 +2,8 @@
 
 use crate::instruction::RewardsInstruction;
 use crate::instructions::*;
+use crate::state::DistributionMode;
 use borsh::BorshDeserialize;
-use everlend_utils::EverlendError;
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::msg;
@@ -36,24 +36,27 @@ pub fn process_instruction(
                 ratio_quote,
                 reward_period_sec,
                 distribution_starts_at,
+                None,
                 reward_max_amount_per_period,
+                DistributionMode::FixedRatio,
             )
         }
         RewardsInstruction::FillVault { amount } => {
             msg!("RewardsInstruction: FillVault");
-            FillVaultContext::new(program_id, accounts)?.process(program_id, amount)
+            FillVaultContext::new_legacy(program_id, accounts)?.process(program_id, amount)
         }
         RewardsInstruction::DepositMining { amount } => {
             msg!("RewardsInstruction: DepositMining");
-            DepositMiningContext::new(program_id, accounts)?.process(program_id, amount)
+            DepositMiningContext::new_legacy(program_id, accounts)?.process(program_id, amount, 0)
         }
         RewardsInstruction::WithdrawMining => {
             msg!("RewardsInstruction: WithdrawMining");
-            WithdrawMiningContext::new(program_id, accounts)?.process(program_id)
+            WithdrawMiningContext::new_legacy(program_id, accounts)?
+                .process(program_id, None, false)
         }
         RewardsInstruction::Claim => {
             msg!("RewardsInstruction: Claim");
-            ClaimContext::new(program_id, accounts)?.process(program_id)
+            ClaimContext::new_legacy(program_id, accounts)?.process(program_id)
         }
         RewardsInstruction::InitializeRoot => {
             msg!("RewardsInstruction: InitializeRoot");
@@ -61,8 +64,210 @@ pub fn process_instruction(
         }
         RewardsInstruction::MigratePool => {
             msg!("RewardsInstruction: MigratePool");
-            Err(EverlendError::NotImplemented.into())
-            // MigratePoolContext::new(program_id, accounts)?.process(program_id)
+            MigratePoolContext::new(program_id, accounts)?.process(program_id, None)
+        }
+        RewardsInstruction::AddFunder => {
+            msg!("RewardsInstruction: AddFunder");
+            AddFunderContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::RemoveFunder => {
+            msg!("RewardsInstruction: RemoveFunder");
+            RemoveFunderContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::UpdateVault {
+            ratio_base,
+            ratio_quote,
+            reward_period_sec,
+            distribution_starts_at,
+            distribution_ends_at,
+            reward_max_amount_per_period,
+        } => {
+            msg!("RewardsInstruction: UpdateVault");
+            UpdateVaultContext::new(program_id, accounts)?.process(
+                program_id,
+                ratio_base,
+                ratio_quote,
+                reward_period_sec,
+                distribution_starts_at,
+                distribution_ends_at,
+                reward_max_amount_per_period,
+            )
+        }
+        RewardsInstruction::SunsetVault { claim_period_sec } => {
+            msg!("RewardsInstruction: SunsetVault");
+            SunsetVaultContext::new(program_id, accounts)?.process(program_id, claim_period_sec)
+        }
+        RewardsInstruction::RemoveVault => {
+            msg!("RewardsInstruction: RemoveVault");
+            RemoveVaultContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::ProposeRootAuthority => {
+            msg!("RewardsInstruction: ProposeRootAuthority");
+            ProposeRootAuthorityContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::AcceptRootAuthority => {
+            msg!("RewardsInstruction: AcceptRootAuthority");
+            AcceptRootAuthorityContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::UpdatePool {
+            lock_time_sec,
+            unbonding_period_sec,
+            early_exit_penalty_bps,
+            penalty_destination,
+            max_vaults,
+        } => {
+            msg!("RewardsInstruction: UpdatePool");
+            UpdatePoolContext::new(program_id, accounts)?.process(
+                program_id,
+                lock_time_sec,
+                unbonding_period_sec,
+                early_exit_penalty_bps,
+                penalty_destination,
+                max_vaults,
+            )
+        }
+        RewardsInstruction::RequestWithdraw { amount } => {
+            msg!("RewardsInstruction: RequestWithdraw");
+            RequestWithdrawContext::new(program_id, accounts)?.process(program_id, amount)
+        }
+        RewardsInstruction::FinalizeWithdraw => {
+            msg!("RewardsInstruction: FinalizeWithdraw");
+            FinalizeWithdrawContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::CancelWithdraw => {
+            msg!("RewardsInstruction: CancelWithdraw");
+            CancelWithdrawContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::SetTreasury => {
+            msg!("RewardsInstruction: SetTreasury");
+            SetTreasuryContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::SetRole { role } => {
+            msg!("RewardsInstruction: SetRole");
+            SetRoleContext::new(program_id, accounts)?.process(program_id, role)
+        }
+        RewardsInstruction::SetRootPaused { paused } => {
+            msg!("RewardsInstruction: SetRootPaused");
+            SetRootPausedContext::new(program_id, accounts)?.process(program_id, paused)
+        }
+        RewardsInstruction::SetPoolPaused { paused } => {
+            msg!("RewardsInstruction: SetPoolPaused");
+            SetPoolPausedContext::new(program_id, accounts)?.process(program_id, paused)
+        }
+        RewardsInstruction::EmergencyWithdraw => {
+            msg!("RewardsInstruction: EmergencyWithdraw");
+            EmergencyWithdrawContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::ProposeAction { action } => {
+            msg!("RewardsInstruction: ProposeAction");
+            ProposeActionContext::new(program_id, accounts)?.process(program_id, action)
+        }
+        RewardsInstruction::ExecuteAction => {
+            msg!("RewardsInstruction: ExecuteAction");
+            ExecuteActionContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::CancelAction => {
+            msg!("RewardsInstruction: CancelAction");
+            CancelActionContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::InitializeMultisig { signers, threshold } => {
+            msg!("RewardsInstruction: InitializeMultisig");
+            InitializeMultisigContext::new(program_id, accounts)?
+                .process(program_id, signers, threshold)
+        }
+        RewardsInstruction::SetDepositAuthority { deposit_authority } => {
+            msg!("RewardsInstruction: SetDepositAuthority");
+            SetDepositAuthorityContext::new(program_id, accounts)?
+                .process(program_id, deposit_authority)
+        }
+        RewardsInstruction::ManagedDeposit { amount } => {
+            msg!("RewardsInstruction: ManagedDeposit");
+            ManagedDepositContext::new(program_id, accounts)?.process(program_id, amount)
+        }
+        RewardsInstruction::ManagedWithdraw { amount } => {
+            msg!("RewardsInstruction: ManagedWithdraw");
+            ManagedWithdrawContext::new(program_id, accounts)?.process(program_id, amount)
+        }
+        RewardsInstruction::TransferMining { amount } => {
+            msg!("RewardsInstruction: TransferMining");
+            TransferMiningContext::new(program_id, accounts)?.process(program_id, amount)
+        }
+        RewardsInstruction::InitializeReceiptMint => {
+            msg!("RewardsInstruction: InitializeReceiptMint");
+            InitializeReceiptMintContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::SettleReceipt => {
+            msg!("RewardsInstruction: SettleReceipt");
+            SettleReceiptContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::MigrateRoot => {
+            msg!("RewardsInstruction: MigrateRoot");
+            MigrateRootContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::WithdrawMiningPartial { amount, forfeit } => {
+            msg!("RewardsInstruction: WithdrawMiningPartial");
+            WithdrawMiningContext::new(program_id, accounts)?.process(
+                program_id,
+                Some(amount),
+                forfeit,
+            )
+        }
+        RewardsInstruction::AddVaultExtended {
+            ratio_base,
+            ratio_quote,
+            reward_period_sec,
+            distribution_starts_at,
+            distribution_ends_at,
+            reward_max_amount_per_period,
+            distribution_mode,
+        } => {
+            msg!("RewardsInstruction: AddVaultExtended");
+            AddVaultContext::new(program_id, accounts)?.process(
+                program_id,
+                ratio_base,
+                ratio_quote,
+                reward_period_sec,
+                distribution_starts_at,
+                distribution_ends_at,
+                reward_max_amount_per_period,
+                distribution_mode,
+            )
+        }
+        RewardsInstruction::FillVaultChecked { amount } => {
+            msg!("RewardsInstruction: FillVaultChecked");
+            FillVaultContext::new(program_id, accounts)?.process(program_id, amount)
+        }
+        RewardsInstruction::ClaimChecked => {
+            msg!("RewardsInstruction: ClaimChecked");
+            ClaimContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::DepositMiningLocked {
+            amount,
+            lock_period_sec,
+        } => {
+            msg!("RewardsInstruction: DepositMiningLocked");
+            DepositMiningContext::new(program_id, accounts)?.process(
+                program_id,
+                amount,
+                lock_period_sec,
+            )
+        }
+        RewardsInstruction::MigratePoolChecked { liabilities } => {
+            msg!("RewardsInstruction: MigratePoolChecked");
+            MigratePoolContext::new(program_id, accounts)?.process(program_id, Some(liabilities))
+        }
+        RewardsInstruction::ClaimAll => {
+            msg!("RewardsInstruction: ClaimAll");
+            ClaimAllContext::new(program_id, accounts)?.process(program_id)
+        }
+        RewardsInstruction::SyncRootPaused => {
+            msg!("RewardsInstruction: SyncRootPaused");
+            SyncRootPausedContext::new(program_id, accounts)?.process()
+        }
+
+        RewardsInstruction::MigrateMining => {
+            msg!("RewardsInstruction: MigrateMining");
+            MigrateMiningContext::new(program_id, accounts)?.process()
         }
     }
 }
@@ -0,0 +1,210 @@
+//! Account layouts of the previous program versions
+
+use crate::state::{
+    AccountType, DistributionMode, LockTranche, Mining, PenaltyDestination, RewardIndex,
+    RewardPool, RewardVault, RewardsRoot, DEFAULT_MAX_VAULTS,
+};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use std::cmp;
+
+/// Max reward vaults of the first layout
+pub const MAX_REWARDS_V1: usize = 5;
+
+/// Rewards root of the first layout
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default)]
+pub struct RewardsRootV1 {
+    /// Account type - RewardsRootV1
+    pub account_type: AccountType,
+    /// Authority address
+    pub authority: Pubkey,
+}
+
+impl RewardsRootV1 {
+    /// LEN
+    pub const LEN: usize = 1 + 32;
+}
+
+/// Upgraded root keeps its deprecated account type until it's migrated
+impl From<RewardsRootV1> for RewardsRoot {
+    fn from(root: RewardsRootV1) -> Self {
+        RewardsRoot {
+            account_type: root.account_type,
+            ..RewardsRoot::init(root.authority)
+        }
+    }
+}
+
+/// Reward pool of the first layout
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default)]
+pub struct RewardPoolV1 {
+    /// Account type - RewardPoolV1
+    pub account_type: AccountType,
+    /// Rewards root account
+    pub rewards_root: Pubkey,
+    /// Saved bump for reward pool account
+    pub bump: u8,
+    /// Liquidity mint
+    pub liquidity_mint: Pubkey,
+    /// Total staked amount
+    pub total_amount: u64,
+    /// staking lock time
+    pub lock_time_sec: u64,
+    /// A set of all possible rewards that we can get for this pool
+    pub vaults: Vec<RewardVaultV1>,
+}
+
+impl RewardPoolV1 {
+    /// LEN
+    pub const LEN: usize = 1 + (32 + 1 + 32 + 8 + 8 + (4 + RewardVaultV1::LEN * MAX_REWARDS_V1));
+}
+
+/// Upgraded pool keeps its deprecated account type until it's migrated.
+/// Funded budgets of the vaults are unknown before the migration
+impl From<RewardPoolV1> for RewardPool {
+    fn from(pool: RewardPoolV1) -> Self {
+        RewardPool {
+            account_type: pool.account_type,
+            rewards_root: pool.rewards_root,
+            bump: pool.bump,
+            liquidity_mint: pool.liquidity_mint,
+            total_amount: pool.total_amount,
+            // there were no boosts
+            total_weighted_amount: pool.total_amount,
+            lock_time_sec: pool.lock_time_sec,
+            unbonding_period_sec: 0,
+            early_exit_penalty_bps: 0,
+            penalty_destination: PenaltyDestination::Treasury,
+            penalty_index_with_precision: 0,
+            paused: false,
+            root_paused: false,
+            deposit_authority: None,
+            receipt_mint: None,
+            max_vaults: cmp::max(DEFAULT_MAX_VAULTS, pool.vaults.len() as u8),
+            // vaults of the first layout and their mining indexes share zero identifier
+            vaults_added: 0,
+            migrated_at: 0,
+            migrated_lock_time_sec: pool.lock_time_sec,
+            vaults: pool.vaults.into_iter().map(RewardVault::from).collect(),
+            funders: vec![],
+        }
+    }
+}
+
+/// Reward vault of the first layout
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone)]
+pub struct RewardVaultV1 {
+    /// Bump of vault account
+    pub bump: u8,
+    /// Reward mint address
+    pub reward_mint: Pubkey,
+    /// Reward ratio of deposit currency
+    pub ratio_base: u64,
+    /// Reward ratio of reward currency
+    pub ratio_quote: u64,
+    /// Time period for reward calculation
+    pub reward_period_sec: u32,
+    /// Timestamp since when distribution begins
+    pub distribution_starts_at: u64,
+    /// Maximum amount of reward per period (cap)
+    pub reward_max_amount_per_period: u64,
+}
+
+impl RewardVaultV1 {
+    /// LEN
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 4 + 8 + 8;
+}
+
+impl From<RewardVaultV1> for RewardVault {
+    fn from(vault: RewardVaultV1) -> Self {
+        RewardVault {
+            bump: vault.bump,
+            reward_mint: vault.reward_mint,
+            ratio_base: vault.ratio_base,
+            ratio_quote: vault.ratio_quote,
+            reward_period_sec: vault.reward_period_sec,
+            distribution_starts_at: vault.distribution_starts_at,
+            reward_max_amount_per_period: vault.reward_max_amount_per_period,
+            distribution_mode: DistributionMode::FixedRatio,
+            ..Default::default()
+        }
+    }
+}
+
+/// Mining of the first layout
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default)]
+pub struct MiningV1 {
+    /// Account type - MiningV1
+    pub account_type: AccountType,
+    /// Reward pool address
+    pub reward_pool: Pubkey,
+    /// Saved bump for mining account
+    pub bump: u8,
+    /// Amount of staked
+    pub amount: u64,
+    /// Last rewards calculation
+    pub rewards_calculated_at: u64,
+    /// Mining owner
+    pub owner: Pubkey,
+    /// last deposit time
+    pub last_deposit_time: u64,
+    /// Reward indexes
+    pub indexes: Vec<RewardIndexV1>,
+}
+
+impl MiningV1 {
+    /// LEN
+    pub const LEN: usize =
+        1 + (32 + 1 + 8 + 8 + 32 + 8 + (4 + RewardIndexV1::LEN * MAX_REWARDS_V1));
+}
+
+/// Mining keeps its deprecated account type until it's converted by the pool,
+/// then it's rewritten with the current layout
+impl From<MiningV1> for Mining {
+    fn from(mining: MiningV1) -> Self {
+        // the whole amount was locked by the pool lock time since the last deposit,
+        // the lock time at the migration is set on conversion
+        let locks = match mining.amount {
+            0 => vec![],
+            amount => vec![LockTranche {
+                amount,
+                deposited_at: mining.last_deposit_time,
+                lock_time_sec: 0,
+                lock_period_sec: 0,
+            }],
+        };
+
+        Mining {
+            account_type: mining.account_type,
+            weighted_amount: mining.amount,
+            amount: mining.amount,
+            rewards_calculated_at: mining.rewards_calculated_at,
+            last_deposit_time: mining.last_deposit_time,
+            locks,
+            indexes: mining
+                .indexes
+                .into_iter()
+                .map(|index| RewardIndex {
+                    reward_mint: index.reward_mint,
+                    rewards: index.rewards,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Mining::initialize(mining.reward_pool, mining.bump, mining.owner)
+        }
+    }
+}
+
+/// Reward index of the first layout
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone)]
+pub struct RewardIndexV1 {
+    /// Reward mint
+    pub reward_mint: Pubkey,
+    /// Rewards amount
+    pub rewards: u64,
+}
+
+impl RewardIndexV1 {
+    /// LEN
+    pub const LEN: usize = 32 + 8;
+}
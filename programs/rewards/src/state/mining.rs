@@ -1,17 +1,27 @@
-use crate::state::{RewardVault, MAX_REWARDS};
+use crate::state::{DistributionMode, MiningV1, RewardVault, BPS_DENOMINATOR, MAX_REWARDS};
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use everlend_utils::cpi::system::realloc_with_rent;
 use everlend_utils::EverlendError;
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack, Sealed};
 use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
 use std::cmp;
-use std::ops::Div;
-use std::slice::Iter;
+use std::slice::IterMut;
 
 use super::AccountType;
 
+/// Max number of locked deposit tranches per mining
+pub const MAX_LOCK_TRANCHES: usize = 5;
+/// Lock period of the max stake boost
+pub const MAX_BOOST_LOCK_SEC: u64 = 365 * 24 * 60 * 60;
+/// Max stake boost in bps (4x)
+pub const MAX_BOOST_BPS: u64 = 40_000;
+
 /// Mining
 #[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default)]
 pub struct Mining {
@@ -23,12 +33,22 @@ pub struct Mining {
     pub bump: u8,
     /// Amount of staked
     pub amount: u64,
+    /// Staked amount boosted by lock periods
+    pub weighted_amount: u64,
     /// Last rewards calculation
     pub rewards_calculated_at: u64,
     /// Mining owner
     pub owner: Pubkey,
     /// last deposit time
     pub last_deposit_time: u64,
+    /// Locked deposit tranches
+    pub locks: Vec<LockTranche>,
+    /// Amount requested for withdrawal, excluded from staked amount
+    pub unbonding_amount: u64,
+    /// Time of the last withdraw request
+    pub unbonding_started_at: u64,
+    /// Checkpoint of pool penalty per share
+    pub penalty_index_with_precision: u128,
     /// Reward indexes
     pub indexes: Vec<RewardIndex>,
 }
@@ -37,40 +57,267 @@ impl Mining {
     /// Initialize a Reward Pool
     pub fn initialize(reward_pool: Pubkey, bump: u8, owner: Pubkey) -> Mining {
         Mining {
-            account_type: AccountType::Mining,
+            account_type: AccountType::MiningV2,
             reward_pool,
             bump,
             amount: 0,
+            weighted_amount: 0,
             rewards_calculated_at: 0,
             last_deposit_time: 0,
             owner,
+            locks: vec![],
+            unbonding_amount: 0,
+            unbonding_started_at: 0,
+            penalty_index_with_precision: 0,
             indexes: vec![],
         }
     }
 
-    /// Returns reward index
-    pub fn reward_index_mut(&mut self, reward_mint: Pubkey) -> &mut RewardIndex {
+    /// Account size with the given number of reward indexes
+    pub fn size(indexes: usize) -> usize {
+        Self::LEN + RewardIndex::LEN * indexes
+    }
+
+    /// Account size of the current reward indexes
+    pub fn packed_len(&self) -> usize {
+        Self::size(self.indexes.len())
+    }
+
+    /// Pack mining, resizing the account to its reward indexes.
+    /// The payer covers rent of added indexes, rent of removed ones is refunded to the owner only
+    pub fn save<'a, 'b>(
+        self,
+        account: &'a AccountInfo<'b>,
+        payer: &'a AccountInfo<'b>,
+    ) -> ProgramResult {
+        let len = self.packed_len();
+        if account.data_len() != len {
+            // otherwise it stays on the account until it's closed to the owner
+            let refund_receiver = if payer.key == &self.owner {
+                Some(payer)
+            } else {
+                None
+            };
+
+            realloc_with_rent(account, payer, refund_receiver, &Rent::get()?, len)?;
+        }
+
+        Self::pack(self, *account.data.borrow_mut())
+    }
+
+    /// Returns reward index of the vault.
+    /// Index left from a removed vault of the same mint is reset
+    pub fn reward_index_mut(&mut self, reward_mint: Pubkey, vault_id: u64) -> &mut RewardIndex {
+        let new_index = RewardIndex {
+            reward_mint,
+            vault_id,
+            ..Default::default()
+        };
+
         match self
             .indexes
             .iter()
             .position(|mi| mi.reward_mint == reward_mint)
         {
-            Some(i) => &mut self.indexes[i],
+            Some(i) => {
+                if self.indexes[i].vault_id != vault_id {
+                    self.indexes[i] = new_index;
+                }
+                &mut self.indexes[i]
+            }
             None => {
-                self.indexes.push(RewardIndex {
-                    reward_mint,
-                    ..Default::default()
-                });
+                self.indexes.push(new_index);
                 self.indexes.last_mut().unwrap()
             }
         }
     }
 
-    /// Flush rewards
-    pub fn flush_rewards(&mut self, reward_mint: Pubkey) -> u64 {
-        let reward_index = self.reward_index_mut(reward_mint);
-        let amount = reward_index.rewards;
-        reward_index.rewards = 0;
+    /// Lock deposited amount with the current pool lock time and the lock period of boost.
+    /// Unlocked tranches are released, deposits are rejected when there are no free slots
+    pub fn lock(
+        &mut self,
+        amount: u64,
+        timestamp: u64,
+        pool_lock_time_sec: u64,
+        lock_period_sec: u64,
+    ) -> ProgramResult {
+        if lock_period_sec > MAX_BOOST_LOCK_SEC {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.locks
+            .retain(|lock| lock.is_locked(pool_lock_time_sec, timestamp));
+
+        if pool_lock_time_sec == 0 && lock_period_sec == 0 {
+            return Ok(());
+        }
+
+        self.push_lock(LockTranche {
+            amount,
+            deposited_at: timestamp,
+            lock_time_sec: pool_lock_time_sec,
+            lock_period_sec,
+        })
+    }
+
+    /// Release locked amount withdrawn early, the oldest tranches first
+    pub fn unlock(&mut self, amount: u64, pool_lock_time_sec: u64, timestamp: u64) {
+        self.locks
+            .retain(|lock| lock.is_locked(pool_lock_time_sec, timestamp));
+
+        let mut amount = amount;
+        for lock in self.locks.iter_mut() {
+            let unlocked_amount = cmp::min(lock.amount, amount);
+            lock.amount -= unlocked_amount;
+            amount -= unlocked_amount;
+        }
+
+        self.locks.retain(|lock| lock.amount > 0);
+    }
+
+    /// Split off locked part of the transferred amount, the unlocked amount goes first
+    /// and the oldest tranches after it
+    pub fn split_locks(
+        &mut self,
+        amount: u64,
+        pool_lock_time_sec: u64,
+        timestamp: u64,
+    ) -> Vec<LockTranche> {
+        let mut locked_amount =
+            amount.saturating_sub(self.unlocked_amount(pool_lock_time_sec, timestamp));
+
+        self.locks
+            .retain(|lock| lock.is_locked(pool_lock_time_sec, timestamp));
+
+        let mut locks = vec![];
+        for lock in self.locks.iter_mut() {
+            if locked_amount == 0 {
+                break;
+            }
+
+            let split_amount = cmp::min(lock.amount, locked_amount);
+            lock.amount -= split_amount;
+            locked_amount -= split_amount;
+
+            locks.push(LockTranche {
+                amount: split_amount,
+                ..lock.clone()
+            });
+        }
+
+        self.locks.retain(|lock| lock.amount > 0);
+
+        locks
+    }
+
+    /// Merge tranches transferred from another mining.
+    /// Transfers are rejected when there are no free slots
+    pub fn merge_locks(
+        &mut self,
+        locks: Vec<LockTranche>,
+        pool_lock_time_sec: u64,
+        timestamp: u64,
+    ) -> ProgramResult {
+        self.locks
+            .retain(|lock| lock.is_locked(pool_lock_time_sec, timestamp));
+
+        for tranche in locks {
+            self.push_lock(tranche)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add lock tranche, existing locks are never extended
+    fn push_lock(&mut self, tranche: LockTranche) -> ProgramResult {
+        if self.locks.len() >= MAX_LOCK_TRANCHES {
+            return Err(EverlendError::LockTranchesFull.into());
+        }
+
+        self.locks.push(tranche);
+
+        Ok(())
+    }
+
+    /// Returns amount available for withdrawal
+    pub fn unlocked_amount(&self, pool_lock_time_sec: u64, timestamp: u64) -> u64 {
+        let locked_amount: u64 = self
+            .locks
+            .iter()
+            .filter(|lock| lock.is_locked(pool_lock_time_sec, timestamp))
+            .map(|lock| lock.amount)
+            .sum();
+
+        self.amount.saturating_sub(locked_amount)
+    }
+
+    /// Calculate staked amount boosted by lock periods of the locked tranches
+    pub fn calculate_weighted_amount(
+        &self,
+        pool_lock_time_sec: u64,
+        timestamp: u64,
+    ) -> Result<u64, ProgramError> {
+        let mut weighted_amount = self.amount;
+
+        for lock in self
+            .locks
+            .iter()
+            .filter(|lock| lock.is_locked(pool_lock_time_sec, timestamp))
+        {
+            weighted_amount = weighted_amount
+                .checked_add(lock.boost()?)
+                .ok_or(EverlendError::MathOverflow)?;
+        }
+
+        Ok(weighted_amount)
+    }
+
+    /// Weighted amount averaged over the time since the last refresh,
+    /// boosts of the tranches unlocked in between count until the unlock only
+    pub fn accrual_weighted_amount(
+        &self,
+        pool_lock_time_sec: u64,
+        timestamp: u64,
+    ) -> Result<u64, ProgramError> {
+        let elapsed = timestamp.saturating_sub(self.rewards_calculated_at);
+        let mut weighted_amount = self.weighted_amount;
+
+        for lock in self.locks.iter() {
+            let unlocks_at = lock.unlocks_at(pool_lock_time_sec);
+            // boost isn't counted or didn't expire yet
+            if unlocks_at <= self.rewards_calculated_at || unlocks_at >= timestamp {
+                continue;
+            }
+
+            let expired_boost = (lock.boost()? as u128)
+                .checked_mul((timestamp - unlocks_at) as u128)
+                .ok_or(EverlendError::MathOverflow)?
+                .checked_div(elapsed as u128)
+                .ok_or(EverlendError::MathOverflow)? as u64;
+
+            weighted_amount = weighted_amount.saturating_sub(expired_boost);
+        }
+
+        Ok(weighted_amount)
+    }
+
+    /// Check if unbonding amount can be released
+    pub fn is_unbonded(&self, unbonding_period_sec: u64, timestamp: u64) -> bool {
+        timestamp.saturating_sub(self.unbonding_started_at) >= unbonding_period_sec
+    }
+
+    /// Check if there is nothing left on the account
+    pub fn is_empty(&self) -> bool {
+        self.amount == 0
+            && self.unbonding_amount == 0
+            && self.indexes.iter().all(|index| index.rewards == 0)
+    }
+
+    /// Flush rewards up to available amount, the remainder is carried forward
+    pub fn flush_rewards(&mut self, vault: &RewardVault, available_amount: u64) -> u64 {
+        let reward_index = self.reward_index_mut(vault.reward_mint, vault.id);
+        let amount = cmp::min(reward_index.rewards, available_amount);
+        reward_index.rewards -= amount;
 
         amount
     }
@@ -78,51 +325,64 @@ impl Mining {
     /// Refresh rewards
     pub fn refresh_rewards(
         &mut self,
-        vaults: Iter<RewardVault>,
+        vaults: IterMut<RewardVault>,
+        current_timestamp: u64,
+    ) -> ProgramResult {
+        self.refresh_weighted_rewards(vaults, self.weighted_amount, current_timestamp)
+    }
+
+    /// Refresh rewards accrued with the passed weighted amount.
+    /// Share of proportional rewards above it returns to the vault budget
+    pub fn refresh_weighted_rewards(
+        &mut self,
+        vaults: IterMut<RewardVault>,
+        amount: u64,
         current_timestamp: u64,
     ) -> ProgramResult {
-        let rewards_calculated_at = self.rewards_calculated_at;
-
-        // first deposit - nothing to calculate
-        if rewards_calculated_at != 0 {
-            let amount = self.amount;
-
-            for vault in vaults {
-                let reward_index = self.reward_index_mut(vault.reward_mint);
-
-                // how much time passed since last reward calculation
-                let reward_period_start =
-                    cmp::max(rewards_calculated_at, vault.distribution_starts_at);
-                let reward_period = current_timestamp.saturating_sub(reward_period_start);
-                let num_periods = reward_period.div(vault.reward_period_sec as u64);
-                if num_periods == 0 {
-                    continue;
+        let weighted_amount = self.weighted_amount;
+        let mut reward_mints = Vec::with_capacity(MAX_REWARDS);
+
+        for vault in vaults {
+            reward_mints.push(vault.reward_mint);
+
+            let reward_index = self.reward_index_mut(vault.reward_mint, vault.id);
+
+            let rewards = match vault.distribution_mode {
+                DistributionMode::FixedRatio => {
+                    let rewards = vault.fixed_ratio_rewards(amount, reward_index)?;
+                    reward_index.index_with_precision = vault.index_with_precision;
+                    reward_index.capped_index_with_precision = vault.capped_index_with_precision;
+                    reward_index.cap_index_with_precision = vault.cap_index_with_precision;
+
+                    // accrual stops once the funded budget is exhausted
+                    vault.accrue(rewards)?
                 }
+                DistributionMode::Proportional => {
+                    let rewards =
+                        vault.proportional_rewards(amount, reward_index.index_with_precision)?;
+                    // index grew with the whole weight including expired boosts
+                    let forfeited_rewards = vault
+                        .proportional_rewards(weighted_amount, reward_index.index_with_precision)?
+                        .saturating_sub(rewards);
+                    vault.release(forfeited_rewards)?;
+                    reward_index.index_with_precision = vault.index_with_precision;
 
-                // calculate reward amount based on coefficient
-                let rewards = (num_periods as u128)
-                    .checked_mul(amount.into())
-                    .ok_or(EverlendError::MathOverflow)?
-                    .checked_mul(vault.ratio_quote.into())
-                    .ok_or(EverlendError::MathOverflow)?
-                    .checked_div(vault.ratio_base.into())
-                    .ok_or(EverlendError::MathOverflow)? as u64;
-
-                if rewards > 0 {
-                    let rewards = if vault.reward_max_amount_per_period > 0 {
-                        std::cmp::min(rewards, vault.reward_max_amount_per_period * num_periods)
-                    } else {
-                        rewards
-                    };
-
-                    reward_index.rewards = reward_index
-                        .rewards
-                        .checked_add(rewards as u64)
-                        .ok_or(EverlendError::MathOverflow)?;
+                    rewards
                 }
+            };
+
+            if rewards > 0 {
+                reward_index.rewards = reward_index
+                    .rewards
+                    .checked_add(rewards)
+                    .ok_or(EverlendError::MathOverflow)?;
             }
         }
 
+        // clean up indexes of removed vaults
+        self.indexes
+            .retain(|reward_index| reward_mints.contains(&reward_index.reward_mint));
+
         // update deposit_timestamp
         self.rewards_calculated_at = current_timestamp;
 
@@ -132,7 +392,40 @@ impl Mining {
 
 impl Sealed for Mining {}
 impl Pack for Mining {
-    const LEN: usize = 1 + (32 + 1 + 8 + 8 + 32 + 8 + (4 + RewardIndex::LEN * MAX_REWARDS));
+    const LEN: usize = 1
+        + (32
+            + 1
+            + 8
+            + 8
+            + 8
+            + 32
+            + 8
+            + (4 + LockTranche::LEN * MAX_LOCK_TRANCHES)
+            + 8
+            + 8
+            + 16
+            + 4);
+
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        // account is sized by its reward indexes
+        Self::unpack_from_slice(input)
+    }
+
+    fn pack(src: Self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() < src.packed_len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // mining of the first layout has to be converted by its pool first
+        if src.account_type == AccountType::MiningV1 {
+            msg!("Mining of the first layout isn't converted");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        src.pack_into_slice(dst);
+
+        Ok(())
+    }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut slice = dst;
@@ -141,7 +434,11 @@ impl Pack for Mining {
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let mut src_mut = src;
-        Self::deserialize(&mut src_mut).map_err(|err| {
+        match AccountType::peek(src)? {
+            AccountType::MiningV1 => MiningV1::deserialize(&mut src_mut).map(Mining::from),
+            _ => Self::deserialize(&mut src_mut),
+        }
+        .map_err(|err| {
             msg!("Failed to deserialize");
             msg!("{}", err.to_string());
             ProgramError::InvalidAccountData
@@ -162,9 +459,75 @@ pub struct RewardIndex {
     pub reward_mint: Pubkey,
     /// Rewards amount
     pub rewards: u64,
+    /// Checkpoint of vault reward per share
+    pub index_with_precision: u128,
+    /// Checkpoint of vault reward per share under the cap (fixed ratio mode)
+    pub capped_index_with_precision: u128,
+    /// Checkpoint of vault cap per mining (fixed ratio mode)
+    pub cap_index_with_precision: u128,
+    /// Identifier of the vault within the pool
+    pub vault_id: u64,
 }
 
 impl RewardIndex {
     ///
-    pub const LEN: usize = 32 + 8;
+    pub const LEN: usize = 32 + 8 + 16 + 16 + 16 + 8;
+}
+
+/// Locked deposit tranche
+#[derive(Debug, BorshSerialize, BorshDeserialize, BorshSchema, Default, Clone)]
+pub struct LockTranche {
+    /// Locked amount
+    pub amount: u64,
+    /// Deposit time
+    pub deposited_at: u64,
+    /// Lock time of the pool at the deposit
+    pub lock_time_sec: u64,
+    /// Lock period chosen for the stake boost
+    pub lock_period_sec: u64,
+}
+
+impl LockTranche {
+    /// LEN
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+
+    /// Check if tranche is still locked.
+    /// Tightened pool lock applies to new deposits only, loosened - to all of them.
+    /// Lock period of the boost can't be loosened
+    pub fn is_locked(&self, pool_lock_time_sec: u64, timestamp: u64) -> bool {
+        let lock_time_sec = cmp::max(
+            cmp::min(self.lock_time_sec, pool_lock_time_sec),
+            self.lock_period_sec,
+        );
+
+        timestamp.saturating_sub(self.deposited_at) < lock_time_sec
+    }
+
+    /// Time when the tranche is unlocked
+    pub fn unlocks_at(&self, pool_lock_time_sec: u64) -> u64 {
+        let lock_time_sec = cmp::max(
+            cmp::min(self.lock_time_sec, pool_lock_time_sec),
+            self.lock_period_sec,
+        );
+
+        self.deposited_at.saturating_add(lock_time_sec)
+    }
+
+    /// Amount added to the stake by the boost
+    pub fn boost(&self) -> Result<u64, ProgramError> {
+        let boost = (self.amount as u128)
+            .checked_mul((self.boost_bps() - BPS_DENOMINATOR) as u128)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(BPS_DENOMINATOR as u128)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(boost as u64)
+    }
+
+    /// Stake boost in bps growing linearly from 1x without lock to max at the max lock period
+    pub fn boost_bps(&self) -> u64 {
+        let lock_period_sec = cmp::min(self.lock_period_sec, MAX_BOOST_LOCK_SEC);
+
+        BPS_DENOMINATOR + (MAX_BOOST_BPS - BPS_DENOMINATOR) * lock_period_sec / MAX_BOOST_LOCK_SEC
+    }
 }
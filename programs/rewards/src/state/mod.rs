@@ -1,13 +1,20 @@
 //! State types
 
+mod deprecated;
 mod mining;
+mod multisig;
+mod pending_action;
 mod reward_pool;
 mod rewards_root;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+pub use deprecated::*;
 pub use mining::*;
+pub use multisig::*;
+pub use pending_action::*;
 pub use reward_pool::*;
 pub use rewards_root::*;
+use solana_program::program_error::ProgramError;
 
 /// Enum representing the account type managed by the program
 #[derive(Default, Clone, Debug, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
@@ -15,10 +22,28 @@ pub enum AccountType {
     /// If the account has not been initialized, the enum will be 0
     #[default]
     Uninitialized,
+    /// Rewards root of the first layout, has to be migrated
+    RewardsRootV1,
+    /// Reward pool of the first layout, has to be migrated
+    RewardPoolV1,
+    /// Mining account of the first layout, migrated on the next update
+    MiningV1,
+    /// Timelocked admin action
+    PendingAction,
+    /// Multisig authority
+    Multisig,
     /// Rewards root
-    RewardsRoot,
+    RewardsRootV2,
     /// Reward pool
-    RewardPool,
+    RewardPoolV2,
     /// Mining account
-    Mining,
+    MiningV2,
+}
+
+impl AccountType {
+    /// Read account type of the account data
+    pub fn peek(src: &[u8]) -> Result<AccountType, ProgramError> {
+        let mut src_mut = src;
+        AccountType::deserialize(&mut src_mut).map_err(|_| ProgramError::InvalidAccountData)
+    }
 }
@@ -0,0 +1,80 @@
+use crate::state::AccountType;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use everlend_utils::MultisigAuthority;
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// Max multisig signers
+pub const MAX_MULTISIG_SIGNERS: usize = 11;
+
+/// M-of-N multisig usable as the root authority
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default)]
+pub struct Multisig {
+    /// Account type - Multisig
+    pub account_type: AccountType,
+    /// Number of signatures required
+    pub threshold: u8,
+    /// Keys allowed to sign
+    pub signers: Vec<Pubkey>,
+}
+
+impl Multisig {
+    /// Init multisig
+    pub fn init(signers: Vec<Pubkey>, threshold: u8) -> Result<Multisig, ProgramError> {
+        let has_duplicates = signers
+            .iter()
+            .enumerate()
+            .any(|(i, signer)| signers[..i].contains(signer));
+
+        if signers.len() > MAX_MULTISIG_SIGNERS
+            || has_duplicates
+            || threshold == 0
+            || threshold as usize > signers.len()
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Multisig {
+            account_type: AccountType::Multisig,
+            threshold,
+            signers,
+        })
+    }
+}
+
+impl MultisigAuthority for Multisig {
+    fn signers(&self) -> &[Pubkey] {
+        &self.signers
+    }
+
+    fn threshold(&self) -> u8 {
+        self.threshold
+    }
+}
+
+impl Sealed for Multisig {}
+impl Pack for Multisig {
+    const LEN: usize = 1 + 1 + (4 + 32 * MAX_MULTISIG_SIGNERS);
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Multisig, ProgramError> {
+        let mut src_mut = src;
+        Self::deserialize(&mut src_mut).map_err(|err| {
+            msg!("Failed to deserialize");
+            msg!("{}", err.to_string());
+            ProgramError::InvalidAccountData
+        })
+    }
+}
+
+impl IsInitialized for Multisig {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::Multisig
+    }
+}
@@ -0,0 +1,197 @@
+use crate::state::{AccountType, PenaltyDestination, Role};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::msg;
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+
+/// Admin action waiting for the root timelock
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct PendingAction {
+    /// Account type - PendingAction
+    pub account_type: AccountType,
+    /// Rewards root
+    pub rewards_root: Pubkey,
+    /// Saved bump for pending action account
+    pub bump: u8,
+    /// Sequential id of the action within the root
+    pub id: u64,
+    /// Account receiving rent when the action is executed or cancelled
+    pub payer: Pubkey,
+    /// Time of proposal
+    pub proposed_at: u64,
+    /// Time since when anyone can execute the action
+    pub executable_at: u64,
+    /// Proposed action
+    pub action: AdminAction,
+}
+
+impl PendingAction {
+    /// Init pending action
+    pub fn init(params: InitPendingActionParams) -> PendingAction {
+        PendingAction {
+            account_type: AccountType::PendingAction,
+            rewards_root: params.rewards_root,
+            bump: params.bump,
+            id: params.id,
+            payer: params.payer,
+            proposed_at: params.proposed_at,
+            executable_at: params.executable_at,
+            action: params.action,
+        }
+    }
+}
+
+/// Initialize a pending action params
+pub struct InitPendingActionParams {
+    /// Rewards root
+    pub rewards_root: Pubkey,
+    /// Saved bump for pending action account
+    pub bump: u8,
+    /// Sequential id of the action within the root
+    pub id: u64,
+    /// Account receiving rent when the action is executed or cancelled
+    pub payer: Pubkey,
+    /// Time of proposal
+    pub proposed_at: u64,
+    /// Time since when anyone can execute the action
+    pub executable_at: u64,
+    /// Proposed action
+    pub action: AdminAction,
+}
+
+/// Parameter changes executed through the root timelock
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Clone, PartialEq, Eq)]
+pub enum AdminAction {
+    /// Changes emission parameters of the vault
+    UpdateVault {
+        /// Reward pool of the vault
+        reward_pool: Pubkey,
+        /// Reward mint of the vault
+        reward_mint: Pubkey,
+        /// Reward ratio of deposit currency
+        ratio_base: u64,
+        /// Reward ratio of reward currency
+        ratio_quote: u64,
+        /// Time period for reward calculation
+        reward_period_sec: u32,
+        /// Timestamp since when distribution begins
+        distribution_starts_at: u64,
+        /// Timestamp when distribution ends (unlimited if not set)
+        distribution_ends_at: Option<u64>,
+        /// Maximum amount of reward per period (cap)
+        reward_max_amount_per_period: u64,
+    },
+    /// Changes lock, penalty and vault limit parameters of the pool
+    UpdatePool {
+        /// Reward pool
+        reward_pool: Pubkey,
+        /// Lock time in seconds
+        lock_time_sec: u64,
+        /// Unbonding period in seconds
+        unbonding_period_sec: u64,
+        /// Penalty of early exit in basis points
+        early_exit_penalty_bps: u16,
+        /// Receiver of early exit penalties
+        penalty_destination: PenaltyDestination,
+        /// Limit of reward vaults
+        max_vaults: u8,
+    },
+    /// Proposes new root authority, it still has to accept it
+    SetAuthority {
+        /// New authority
+        new_authority: Pubkey,
+    },
+    /// Sweeps leftovers of the ended vault, closes it and frees the slot
+    RemoveVault {
+        /// Reward pool of the vault
+        reward_pool: Pubkey,
+        /// Reward mint of the vault
+        reward_mint: Pubkey,
+        /// Token account receiving leftovers
+        destination: Pubkey,
+    },
+    /// Changes delay of the root timelock
+    SetTimelock {
+        /// Delay in seconds
+        timelock_sec: u64,
+    },
+    /// Ends distribution of the vault and starts its claim period
+    SunsetVault {
+        /// Reward pool of the vault
+        reward_pool: Pubkey,
+        /// Reward mint of the vault
+        reward_mint: Pubkey,
+        /// Time given to users to claim accrued rewards
+        claim_period_sec: u64,
+    },
+    /// Changes treasury receiving early exit penalties
+    SetTreasury {
+        /// New treasury
+        treasury: Pubkey,
+    },
+    /// Changes account of the root role
+    SetRole {
+        /// Role to set
+        role: Role,
+        /// Account of the role
+        account: Pubkey,
+    },
+    /// Switches the pool to managed mode or back
+    SetDepositAuthority {
+        /// Reward pool
+        reward_pool: Pubkey,
+        /// Program account managing balances
+        deposit_authority: Option<Pubkey>,
+    },
+}
+
+impl AdminAction {
+    /// Role allowed to propose the action, authority-only if not set
+    pub fn role(&self) -> Option<Role> {
+        match self {
+            AdminAction::UpdateVault { .. }
+            | AdminAction::RemoveVault { .. }
+            | AdminAction::SunsetVault { .. } => Some(Role::VaultManager),
+            _ => None,
+        }
+    }
+
+    /// Reward pool affected by the action
+    pub fn reward_pool(&self) -> Option<&Pubkey> {
+        match self {
+            AdminAction::UpdateVault { reward_pool, .. }
+            | AdminAction::UpdatePool { reward_pool, .. }
+            | AdminAction::RemoveVault { reward_pool, .. }
+            | AdminAction::SunsetVault { reward_pool, .. }
+            | AdminAction::SetDepositAuthority { reward_pool, .. } => Some(reward_pool),
+            _ => None,
+        }
+    }
+}
+
+impl Sealed for PendingAction {}
+impl Pack for PendingAction {
+    // size of the largest action
+    const LEN: usize = 1 + 32 + 1 + 8 + 32 + 8 + 8 + (1 + 32 + 32 + 8 + 8 + 4 + 8 + 9 + 8);
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<PendingAction, ProgramError> {
+        let mut src_mut = src;
+        Self::deserialize(&mut src_mut).map_err(|err| {
+            msg!("Failed to deserialize");
+            msg!("{}", err.to_string());
+            ProgramError::InvalidAccountData
+        })
+    }
+}
+
+impl IsInitialized for PendingAction {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::PendingAction
+    }
+}
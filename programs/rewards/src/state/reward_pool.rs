@@ -1,16 +1,30 @@
-use crate::state::{AccountType, Mining};
+use crate::state::{AccountType, Mining, RewardIndex, RewardPoolV1, RewardsRoot};
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use everlend_utils::cpi::system::realloc_with_rent;
 use everlend_utils::EverlendError;
+use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack, Sealed};
 use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+use std::cmp;
+use std::convert::TryFrom;
 
 /// Precision for index calculation
 pub const PRECISION: u128 = 10_000_000_000_000_000;
 /// Max reward vaults
-pub const MAX_REWARDS: usize = 5;
+pub const MAX_REWARDS: usize = 32;
+/// Default limit of reward vaults of the pool
+pub const DEFAULT_MAX_VAULTS: u8 = 5;
+/// Max approved funders
+pub const MAX_FUNDERS: usize = 5;
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+/// Min time given to users to claim rewards of the ended vault
+pub const MIN_CLAIM_PERIOD_SEC: u64 = 7 * 24 * 60 * 60;
 
 /// Reward pool
 #[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default)]
@@ -25,28 +39,135 @@ pub struct RewardPool {
     pub liquidity_mint: Pubkey,
     /// Total staked amount
     pub total_amount: u64,
+    /// Total staked amount boosted by lock periods
+    pub total_weighted_amount: u64,
     /// staking lock time
     pub lock_time_sec: u64,
+    /// Cooldown of the requested withdrawals, zero for instant withdrawals
+    pub unbonding_period_sec: u64,
+    /// Penalty of withdrawing locked amount in bps, zero for hard lock
+    pub early_exit_penalty_bps: u16,
+    /// Receiver of early exit penalties
+    pub penalty_destination: PenaltyDestination,
+    /// Accumulated penalty per share shared with stakers
+    pub penalty_index_with_precision: u128,
+    /// Operations of the pool are paused
+    pub paused: bool,
+    /// Pause of the rewards root synced to the pool for legacy instructions
+    pub root_paused: bool,
+    /// Program account adjusting balances of the managed pool without token transfers
+    pub deposit_authority: Option<Pubkey>,
+    /// Mint of transferable stake receipts, minted 1:1 on deposit
+    pub receipt_mint: Option<Pubkey>,
+    /// Limit of reward vaults, the account grows with added vaults
+    pub max_vaults: u8,
+    /// Number of vaults ever added, identifies vaults re-added with the same mint
+    pub vaults_added: u64,
+    /// Time of the migration from the first layout, zero for pools created with the current one
+    pub migrated_at: u64,
+    /// Lock time of the pool at the migration, kept by deposits of the first layout
+    pub migrated_lock_time_sec: u64,
     /// A set of all possible rewards that we can get for this pool
     pub vaults: Vec<RewardVault>,
+    /// Accounts allowed to fill vaults
+    pub funders: Vec<Pubkey>,
 }
 
 impl RewardPool {
     /// Init reward pool
     pub fn init(params: InitRewardPoolParams) -> RewardPool {
         RewardPool {
-            account_type: AccountType::RewardPool,
+            account_type: AccountType::RewardPoolV2,
             rewards_root: params.rewards_root,
             bump: params.bump,
             liquidity_mint: params.liquidity_mint,
             total_amount: 0,
+            total_weighted_amount: 0,
             lock_time_sec: params.lock_time_sec,
+            unbonding_period_sec: 0,
+            early_exit_penalty_bps: 0,
+            penalty_destination: PenaltyDestination::Treasury,
+            penalty_index_with_precision: 0,
+            paused: false,
+            root_paused: false,
+            deposit_authority: None,
+            receipt_mint: None,
+            max_vaults: DEFAULT_MAX_VAULTS,
+            vaults_added: 0,
+            migrated_at: 0,
+            migrated_lock_time_sec: 0,
             vaults: vec![],
+            funders: vec![],
         }
     }
 
+    /// Check if the pool or its root are paused
+    pub fn is_paused(&self, rewards_root: &RewardsRoot) -> bool {
+        self.paused || rewards_root.paused
+    }
+
+    /// Check that neither the pool nor its root are paused
+    pub fn assert_not_paused(&self, rewards_root: &RewardsRoot) -> ProgramResult {
+        if self.is_paused(rewards_root) {
+            return Err(EverlendError::Paused.into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that neither the pool nor its synced root pause are set,
+    /// used by legacy instructions without the rewards root
+    pub fn assert_pool_not_paused(&self) -> ProgramResult {
+        if self.paused || self.root_paused {
+            return Err(EverlendError::Paused.into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that balances of the pool are backed by staked tokens
+    pub fn assert_not_managed(&self) -> ProgramResult {
+        if self.deposit_authority.is_some() {
+            return Err(EverlendError::ManagedPool.into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that the pool has no receipt mint
+    pub fn assert_no_receipts(&self) -> ProgramResult {
+        if self.receipt_mint.is_some() {
+            return Err(EverlendError::ReceiptPool.into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that stake of the pool can be freely transferred with receipts.
+    /// Locks, unbonding and penalties can be bypassed by a receipt transfer
+    pub fn assert_liquid(&self) -> ProgramResult {
+        if self.lock_time_sec > 0
+            || self.unbonding_period_sec > 0
+            || self.early_exit_penalty_bps > 0
+        {
+            return Err(EverlendError::ReceiptPool.into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that the account is deposit authority of the managed pool
+    pub fn assert_deposit_authority(&self, account: &Pubkey) -> ProgramResult {
+        if self.deposit_authority.as_ref() != Some(account) {
+            msg!("Account {} is not a deposit authority of the pool", account);
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+
     /// Process add vault
-    pub fn add_vault(&mut self, reward: RewardVault) -> ProgramResult {
+    pub fn add_vault(&mut self, mut reward: RewardVault) -> ProgramResult {
         if self
             .vaults
             .iter()
@@ -55,14 +176,368 @@ impl RewardPool {
             return Err(ProgramError::InvalidArgument);
         }
 
+        if self.vaults.len() >= self.max_vaults as usize {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.vaults_added = self
+            .vaults_added
+            .checked_add(1)
+            .ok_or(EverlendError::MathOverflow)?;
+        reward.id = self.vaults_added;
         self.vaults.push(reward);
 
         Ok(())
     }
 
-    /// Process deposit
-    pub fn deposit(&mut self, mining: &mut Mining, amount: u64, timestamp: u64) -> ProgramResult {
-        mining.refresh_rewards(self.vaults.iter(), timestamp)?;
+    /// Process add funder
+    pub fn add_funder(&mut self, funder: Pubkey) -> ProgramResult {
+        if self.funders.len() >= MAX_FUNDERS || self.funders.contains(&funder) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.funders.push(funder);
+
+        Ok(())
+    }
+
+    /// Process remove funder
+    pub fn remove_funder(&mut self, funder: Pubkey) -> ProgramResult {
+        let index = self
+            .funders
+            .iter()
+            .position(|f| *f == funder)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        self.funders.remove(index);
+
+        Ok(())
+    }
+
+    /// Process remove vault
+    pub fn remove_vault(&mut self, reward_mint: Pubkey) -> Result<RewardVault, ProgramError> {
+        let index = self
+            .vaults
+            .iter()
+            .position(|v| v.reward_mint == reward_mint)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        Ok(self.vaults.remove(index))
+    }
+
+    /// End distribution of the vault, accruing proportional rewards until the end
+    pub fn sunset_vault(
+        &mut self,
+        reward_mint: Pubkey,
+        timestamp: u64,
+        claim_period_sec: u64,
+    ) -> ProgramResult {
+        self.refresh_vaults(timestamp)?;
+
+        let vault = self
+            .vaults
+            .iter_mut()
+            .find(|v| v.reward_mint == reward_mint)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        vault.sunset(timestamp, claim_period_sec)?;
+
+        msg!(
+            "Vault {} ended. Rewards can be claimed until {}",
+            vault.reward_mint,
+            vault.claim_deadline
+        );
+
+        Ok(())
+    }
+
+    /// Update emission parameters of the vault, accruing rewards with the current ones
+    pub fn update_vault(&mut self, reward_mint: Pubkey, params: VaultCheckpoint) -> ProgramResult {
+        let timestamp = params.updated_at;
+
+        // accrue proportional rewards with current parameters
+        self.refresh_vaults(timestamp)?;
+
+        let vault = self
+            .vaults
+            .iter_mut()
+            .find(|v| v.reward_mint == reward_mint)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        if vault.is_ended() {
+            return Err(EverlendError::RewardVaultEnded.into());
+        }
+
+        if params.distribution_starts_at != vault.distribution_starts_at
+            && params.distribution_starts_at < timestamp
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if let Some(ends_at) = params.distribution_ends_at {
+            let ends_in_past =
+                params.distribution_ends_at != vault.distribution_ends_at && ends_at < timestamp;
+            if ends_in_past || ends_at <= params.distribution_starts_at {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        let invalid_emission = match vault.distribution_mode {
+            DistributionMode::FixedRatio => params.ratio_base == 0,
+            DistributionMode::Proportional => params.reward_max_amount_per_period == 0,
+        };
+        if params.reward_period_sec == 0 || invalid_emission {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        msg!(
+            "Vault {} updated: ratio {}/{}, period {}, starts at {}, ends at {:?}, max amount {}",
+            vault.reward_mint,
+            params.ratio_base,
+            params.ratio_quote,
+            params.reward_period_sec,
+            params.distribution_starts_at,
+            params.distribution_ends_at,
+            params.reward_max_amount_per_period
+        );
+
+        vault.update(params);
+
+        Ok(())
+    }
+
+    /// Update lock and penalty parameters
+    pub fn update_params(
+        &mut self,
+        lock_time_sec: u64,
+        unbonding_period_sec: u64,
+        early_exit_penalty_bps: u16,
+        penalty_destination: PenaltyDestination,
+        max_vaults: u8,
+    ) -> ProgramResult {
+        if early_exit_penalty_bps as u64 > BPS_DENOMINATOR {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if max_vaults as usize > MAX_REWARDS || (max_vaults as usize) < self.vaults.len() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if self.receipt_mint.is_some()
+            && (lock_time_sec > 0 || unbonding_period_sec > 0 || early_exit_penalty_bps > 0)
+        {
+            return Err(EverlendError::ReceiptPool.into());
+        }
+
+        msg!(
+            "Pool lock time changed from {} to {}",
+            self.lock_time_sec,
+            lock_time_sec
+        );
+
+        msg!(
+            "Pool unbonding period changed from {} to {}",
+            self.unbonding_period_sec,
+            unbonding_period_sec
+        );
+
+        msg!(
+            "Pool early exit penalty changed from {} to {} bps, destination {:?}",
+            self.early_exit_penalty_bps,
+            early_exit_penalty_bps,
+            penalty_destination
+        );
+
+        self.lock_time_sec = lock_time_sec;
+        self.unbonding_period_sec = unbonding_period_sec;
+        self.early_exit_penalty_bps = early_exit_penalty_bps;
+        self.penalty_destination = penalty_destination;
+
+        msg!(
+            "Pool vault limit changed from {} to {}",
+            self.max_vaults,
+            max_vaults
+        );
+
+        self.max_vaults = max_vaults;
+
+        Ok(())
+    }
+
+    /// Switch the pool to managed mode or back, only while it has no stakers
+    pub fn set_deposit_authority(&mut self, deposit_authority: Option<Pubkey>) -> ProgramResult {
+        self.assert_no_receipts()?;
+
+        // staked tokens and managed balances can't be mixed
+        if self.total_amount > 0 {
+            msg!("Pool mode can't be changed while it has stakers");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        msg!(
+            "Pool deposit authority changed from {:?} to {:?}",
+            self.deposit_authority,
+            deposit_authority
+        );
+
+        self.deposit_authority = deposit_authority;
+
+        Ok(())
+    }
+
+    /// Process update of proportional vault indexes
+    pub fn refresh_vaults(&mut self, timestamp: u64) -> ProgramResult {
+        let total_weighted_amount = self.total_weighted_amount;
+
+        for vault in self.vaults.iter_mut() {
+            vault.refresh_index(total_weighted_amount, timestamp)?;
+        }
+
+        Ok(())
+    }
+
+    /// Convert mining of the first layout. Its unclaimed rewards and rewards of the whole periods
+    /// up to the migration are taken from the reserve of the vault, then from its budget
+    pub fn migrate_mining(&mut self, mining: &mut Mining) -> ProgramResult {
+        if mining.account_type != AccountType::MiningV1 {
+            return Ok(());
+        }
+
+        let amount = mining.amount;
+        let rewards_calculated_at = mining.rewards_calculated_at;
+
+        // vaults of the first layout share zero identifier
+        for vault in self.vaults.iter_mut().filter(|v| v.id == 0) {
+            let reward_index = mining.reward_index_mut(vault.reward_mint, vault.id);
+
+            let rewards = vault
+                .legacy_rewards(amount, rewards_calculated_at, self.migrated_at)?
+                .checked_add(reward_index.rewards)
+                .ok_or(EverlendError::MathOverflow)?;
+
+            let reserved_rewards = cmp::min(rewards, vault.legacy_reserve);
+            vault.legacy_reserve -= reserved_rewards;
+            let accrued_rewards = vault.accrue(rewards - reserved_rewards)?;
+
+            reward_index.rewards = reserved_rewards
+                .checked_add(accrued_rewards)
+                .ok_or(EverlendError::MathOverflow)?;
+        }
+
+        for lock in mining.locks.iter_mut() {
+            lock.lock_time_sec = self.migrated_lock_time_sec;
+        }
+
+        mining.account_type = AccountType::MiningV2;
+
+        Ok(())
+    }
+
+    /// Refresh rewards of the mining and compound penalties shared with stakers
+    pub fn refresh_mining(&mut self, mining: &mut Mining, timestamp: u64) -> ProgramResult {
+        self.refresh_capped_mining(mining, u64::MAX, timestamp)
+    }
+
+    /// Refresh rewards of the mining accruing to no more than the passed weighted amount
+    fn refresh_capped_mining(
+        &mut self,
+        mining: &mut Mining,
+        max_weighted_amount: u64,
+        timestamp: u64,
+    ) -> ProgramResult {
+        self.migrate_mining(mining)?;
+        self.refresh_vaults(timestamp)?;
+        // expired boosts don't earn past the unlock
+        let weighted_amount = cmp::min(
+            mining.accrual_weighted_amount(self.lock_time_sec, timestamp)?,
+            max_weighted_amount,
+        );
+        mining.refresh_weighted_rewards(self.vaults.iter_mut(), weighted_amount, timestamp)?;
+
+        let penalty_share = self
+            .penalty_index_with_precision
+            .checked_sub(mining.penalty_index_with_precision)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_mul(mining.amount as u128)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(PRECISION)
+            .ok_or(EverlendError::MathOverflow)? as u64;
+
+        mining.amount = mining
+            .amount
+            .checked_add(penalty_share)
+            .ok_or(EverlendError::MathOverflow)?;
+        mining.penalty_index_with_precision = self.penalty_index_with_precision;
+
+        self.refresh_weighted_amount(mining, timestamp)
+    }
+
+    /// Recalculate weighted amount of the mining, boosts of unlocked tranches are dropped
+    pub fn refresh_weighted_amount(
+        &mut self,
+        mining: &mut Mining,
+        timestamp: u64,
+    ) -> ProgramResult {
+        let weighted_amount = mining.calculate_weighted_amount(self.lock_time_sec, timestamp)?;
+
+        self.total_weighted_amount = self
+            .total_weighted_amount
+            .checked_sub(mining.weighted_amount)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_add(weighted_amount)
+            .ok_or(EverlendError::MathOverflow)?;
+        mining.weighted_amount = weighted_amount;
+
+        Ok(())
+    }
+
+    /// Calculate penalty of withdrawing locked amount
+    pub fn early_exit_penalty(&self, locked_amount: u64) -> Result<u64, ProgramError> {
+        let penalty = (locked_amount as u128)
+            .checked_mul(self.early_exit_penalty_bps as u128)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(BPS_DENOMINATOR as u128)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(penalty as u64)
+    }
+
+    /// Share penalty with the remaining stakers, it's compounded into their stake.
+    /// Returns shared amount, nothing is shared without stakers
+    pub fn share_penalty(&mut self, penalty: u64) -> Result<u64, ProgramError> {
+        if self.total_amount == 0 {
+            return Ok(0);
+        }
+
+        let index = (penalty as u128)
+            .checked_mul(PRECISION)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(self.total_amount as u128)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        self.penalty_index_with_precision = self
+            .penalty_index_with_precision
+            .checked_add(index)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        self.total_amount = self
+            .total_amount
+            .checked_add(penalty)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(penalty)
+    }
+
+    /// Process deposit.
+    /// Amount is locked for the pool lock time or the longer lock period boosting the stake
+    pub fn deposit(
+        &mut self,
+        mining: &mut Mining,
+        amount: u64,
+        lock_period_sec: u64,
+        timestamp: u64,
+    ) -> ProgramResult {
+        self.refresh_mining(mining, timestamp)?;
 
         self.total_amount = self
             .total_amount
@@ -75,34 +550,194 @@ impl RewardPool {
             .ok_or(EverlendError::MathOverflow)?;
 
         mining.last_deposit_time = timestamp;
+        mining.lock(amount, timestamp, self.lock_time_sec, lock_period_sec)?;
 
-        Ok(())
+        self.refresh_weighted_amount(mining, timestamp)
     }
 
     /// Process withdraw
-    pub fn withdraw(&mut self, amount: u64) -> ProgramResult {
+    pub fn withdraw(&mut self, mining: &mut Mining, amount: u64, timestamp: u64) -> ProgramResult {
+        self.refresh_mining(mining, timestamp)?;
+
         self.total_amount = self
             .total_amount
             .checked_sub(amount)
             .ok_or(EverlendError::MathOverflow)?;
 
+        mining.amount = mining
+            .amount
+            .checked_sub(amount)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        self.refresh_weighted_amount(mining, timestamp)
+    }
+
+    /// Process transfer of staked amount between minings.
+    /// Locked tranches and a proportional share of unclaimed rewards follow the amount
+    pub fn transfer(
+        &mut self,
+        source: &mut Mining,
+        destination: &mut Mining,
+        amount: u64,
+        timestamp: u64,
+    ) -> ProgramResult {
+        self.refresh_mining(source, timestamp)?;
+        self.refresh_mining(destination, timestamp)?;
+
+        if amount > source.amount {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        for reward_index in source.indexes.iter_mut() {
+            let rewards = (reward_index.rewards as u128)
+                .checked_mul(amount as u128)
+                .ok_or(EverlendError::MathOverflow)?
+                .checked_div(source.amount as u128)
+                .ok_or(EverlendError::MathOverflow)? as u64;
+
+            reward_index.rewards -= rewards;
+
+            let destination_index =
+                destination.reward_index_mut(reward_index.reward_mint, reward_index.vault_id);
+            destination_index.rewards = destination_index
+                .rewards
+                .checked_add(rewards)
+                .ok_or(EverlendError::MathOverflow)?;
+        }
+
+        let locks = source.split_locks(amount, self.lock_time_sec, timestamp);
+        destination.merge_locks(locks, self.lock_time_sec, timestamp)?;
+
+        source.amount -= amount;
+        destination.amount = destination
+            .amount
+            .checked_add(amount)
+            .ok_or(EverlendError::MathOverflow)?;
+        destination.last_deposit_time =
+            cmp::max(destination.last_deposit_time, source.last_deposit_time);
+
+        self.refresh_weighted_amount(source, timestamp)?;
+        self.refresh_weighted_amount(destination, timestamp)
+    }
+
+    /// Process settlement of the mining with its receipt balance.
+    /// Receipts could move at any time since the last refresh, so rewards up to now
+    /// are accrued to the lesser of the previous amount and the balance
+    pub fn settle(&mut self, mining: &mut Mining, balance: u64, timestamp: u64) -> ProgramResult {
+        // pools with receipts have no boosts, weighted amount is the staked amount
+        self.refresh_capped_mining(mining, balance, timestamp)?;
+
+        self.total_amount = self
+            .total_amount
+            .checked_sub(mining.amount)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_add(balance)
+            .ok_or(EverlendError::MathOverflow)?;
+        mining.amount = balance;
+
+        self.refresh_weighted_amount(mining, timestamp)
+    }
+
+    /// Process withdraw request.
+    /// Requested amount stops earning rewards and is released after the unbonding period
+    pub fn request_withdraw(
+        &mut self,
+        mining: &mut Mining,
+        amount: u64,
+        timestamp: u64,
+    ) -> ProgramResult {
+        self.withdraw(mining, amount, timestamp)?;
+
+        mining.unbonding_amount = mining
+            .unbonding_amount
+            .checked_add(amount)
+            .ok_or(EverlendError::MathOverflow)?;
+        mining.unbonding_started_at = timestamp;
+
         Ok(())
     }
 
-    /// Process migrate
-    pub fn migrate(deprecated_pool: &RewardPool) -> RewardPool {
-        Self {
-            account_type: AccountType::RewardPool,
-            rewards_root: deprecated_pool.rewards_root,
-            bump: deprecated_pool.bump,
-            liquidity_mint: deprecated_pool.liquidity_mint,
-            total_amount: deprecated_pool.total_amount,
-            lock_time_sec: deprecated_pool.lock_time_sec,
-            vaults: deprecated_pool.vaults.clone(),
+    /// Process cancel of withdraw request.
+    /// Unbonding amount is staked back without a new lock
+    pub fn cancel_withdraw(&mut self, mining: &mut Mining, timestamp: u64) -> ProgramResult {
+        self.refresh_mining(mining, timestamp)?;
+
+        self.total_amount = self
+            .total_amount
+            .checked_add(mining.unbonding_amount)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        mining.amount = mining
+            .amount
+            .checked_add(mining.unbonding_amount)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        mining.unbonding_amount = 0;
+        mining.unbonding_started_at = 0;
+
+        self.refresh_weighted_amount(mining, timestamp)
+    }
+
+    /// Process claim of rewards.
+    /// Pays no more than available amount, the remainder is left for next claims
+    pub fn claim(
+        &mut self,
+        mining: &mut Mining,
+        reward_mint: Pubkey,
+        available_amount: u64,
+    ) -> Result<u64, ProgramError> {
+        let vault = self
+            .vaults
+            .iter_mut()
+            .find(|v| v.reward_mint == reward_mint)
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        let amount = mining.flush_rewards(vault, available_amount);
+
+        vault.total_claimed = vault
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(amount)
+    }
+
+    /// Account size with the given number of vaults
+    pub fn size(vaults: usize) -> usize {
+        Self::LEN + RewardVault::LEN * vaults
+    }
+
+    /// Account size of the current vaults
+    pub fn packed_len(&self) -> usize {
+        Self::size(self.vaults.len())
+    }
+
+    /// Pack reward pool, resizing the account to its vaults.
+    /// The payer covers rent of added vaults, rent of removed ones stays on the pool for new ones
+    pub fn save<'a, 'b>(
+        self,
+        account: &'a AccountInfo<'b>,
+        payer: &'a AccountInfo<'b>,
+    ) -> ProgramResult {
+        let len = self.packed_len();
+        if account.data_len() != len {
+            realloc_with_rent(account, payer, None, &Rent::get()?, len)?;
         }
+
+        Self::pack(self, *account.data.borrow_mut())
     }
 }
 
+/// Receiver of early exit penalties
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone, PartialEq, Eq)]
+pub enum PenaltyDestination {
+    /// Treasury token account of the rewards root
+    #[default]
+    Treasury,
+    /// Remaining stakers of the pool
+    Stakers,
+}
+
 /// Initialize a Reward Pool params
 pub struct InitRewardPoolParams {
     /// Rewards Root
@@ -117,7 +752,42 @@ pub struct InitRewardPoolParams {
 
 impl Sealed for RewardPool {}
 impl Pack for RewardPool {
-    const LEN: usize = 1 + (32 + 1 + 32 + 8 + 8 + (4 + RewardVault::LEN * MAX_REWARDS));
+    const LEN: usize = 1
+        + (32
+            + 1
+            + 32
+            + 8
+            + 8
+            + 8
+            + 8
+            + 2
+            + 1
+            + 16
+            + 1
+            + 1
+            + (1 + 32)
+            + (1 + 32)
+            + 1
+            + 8
+            + 8
+            + 8
+            + 4
+            + (4 + 32 * MAX_FUNDERS));
+
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        // account is sized by its vaults
+        Self::unpack_from_slice(input)
+    }
+
+    fn pack(src: Self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() < src.packed_len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        src.pack_into_slice(dst);
+
+        Ok(())
+    }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut slice = dst;
@@ -126,7 +796,13 @@ impl Pack for RewardPool {
 
     fn unpack_from_slice(src: &[u8]) -> Result<RewardPool, ProgramError> {
         let mut src_mut = src;
-        Self::deserialize(&mut src_mut).map_err(|err| {
+        match AccountType::peek(src)? {
+            AccountType::RewardPoolV1 => {
+                RewardPoolV1::deserialize(&mut src_mut).map(RewardPool::from)
+            }
+            _ => Self::deserialize(&mut src_mut),
+        }
+        .map_err(|err| {
             msg!("Failed to deserialize");
             msg!("{}", err.to_string());
             ProgramError::InvalidAccountData
@@ -136,7 +812,7 @@ impl Pack for RewardPool {
 
 impl IsInitialized for RewardPool {
     fn is_initialized(&self) -> bool {
-        self.account_type == AccountType::RewardPool
+        self.account_type == AccountType::RewardPoolV2
     }
 }
 
@@ -155,11 +831,370 @@ pub struct RewardVault {
     pub reward_period_sec: u32,
     /// Timestamp since when distribution begins
     pub distribution_starts_at: u64,
-    /// Maximum amount of reward per period (cap)
+    /// Timestamp when distribution ends (unlimited if not set)
+    pub distribution_ends_at: Option<u64>,
+    /// Maximum amount of reward per period (cap).
+    /// For proportional vaults it's the amount distributed per period between all stakers.
     pub reward_max_amount_per_period: u64,
+    /// Distribution mode
+    pub distribution_mode: DistributionMode,
+    /// Accumulated reward per share
+    pub index_with_precision: u128,
+    /// Part of the fixed ratio index accumulated under the cap
+    pub capped_index_with_precision: u128,
+    /// Accumulated cap of rewards per mining (fixed ratio mode)
+    pub cap_index_with_precision: u128,
+    /// Last index calculation
+    pub index_updated_at: u64,
+    /// Total amount of rewards filled into the vault
+    pub total_funded: u64,
+    /// Total amount of rewards paid out to users
+    pub total_claimed: u64,
+    /// Total amount of rewards accrued to users
+    pub total_accrued: u64,
+    /// Time when distribution was ended (0 if active)
+    pub ended_at: u64,
+    /// Time until users can claim rewards of ended vault
+    pub claim_deadline: u64,
+    /// Identifier of the vault within the pool
+    pub id: u64,
+    /// Rewards reserved at the migration for minings of the first layout not converted yet
+    pub legacy_reserve: u64,
 }
 
 impl RewardVault {
     /// LEN
-    pub const LEN: usize = 1 + 32 + 8 + 8 + 4 + 8 + 8;
+    pub const LEN: usize =
+        1 + 32 + 8 + 8 + 4 + 8 + 9 + 8 + 1 + 16 + 16 + 16 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8;
+
+    /// Check if distribution is ended
+    pub fn is_ended(&self) -> bool {
+        self.ended_at > 0
+    }
+
+    /// Latest timestamp rewards are accrued for
+    pub fn accrual_timestamp(&self, timestamp: u64) -> u64 {
+        let timestamp = match self.distribution_ends_at {
+            Some(distribution_ends_at) => cmp::min(timestamp, distribution_ends_at),
+            None => timestamp,
+        };
+
+        if self.is_ended() {
+            return cmp::min(timestamp, self.ended_at);
+        }
+
+        timestamp
+    }
+
+    /// End distribution and give users time to claim accrued rewards
+    pub fn sunset(&mut self, timestamp: u64, claim_period_sec: u64) -> ProgramResult {
+        if self.is_ended() {
+            return Err(EverlendError::RewardVaultEnded.into());
+        }
+
+        if claim_period_sec < MIN_CLAIM_PERIOD_SEC {
+            return Err(EverlendError::ClaimPeriodTooShort.into());
+        }
+
+        self.ended_at = timestamp;
+        self.claim_deadline = timestamp
+            .checked_add(claim_period_sec)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Amount of funded rewards that is not accrued yet
+    pub fn available_budget(&self) -> u64 {
+        self.total_funded.saturating_sub(self.total_accrued)
+    }
+
+    /// Accrued but not claimed rewards
+    pub fn liabilities(&self) -> u64 {
+        self.total_accrued.saturating_sub(self.total_claimed)
+    }
+
+    /// Return accrued rewards nobody is entitled to back to the budget
+    pub fn release(&mut self, rewards: u64) -> ProgramResult {
+        self.total_accrued = self
+            .total_accrued
+            .checked_sub(rewards)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Accrue rewards within the funded budget. Returns accrued amount
+    pub fn accrue(&mut self, rewards: u64) -> Result<u64, ProgramError> {
+        let rewards = cmp::min(rewards, self.available_budget());
+
+        self.total_accrued = self
+            .total_accrued
+            .checked_add(rewards)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(rewards)
+    }
+
+    /// Calculate rewards of the first layout for the whole periods between the timestamps
+    pub fn legacy_rewards(&self, amount: u64, from: u64, to: u64) -> Result<u64, ProgramError> {
+        // rewards weren't calculated before the first deposit
+        if from == 0 || self.ratio_base == 0 || self.reward_period_sec == 0 {
+            return Ok(0);
+        }
+
+        let period_start = cmp::max(from, self.distribution_starts_at);
+        let num_periods = to.saturating_sub(period_start) / self.reward_period_sec as u64;
+
+        let rewards = (num_periods as u128)
+            .checked_mul(amount.into())
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_mul(self.ratio_quote.into())
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(self.ratio_base.into())
+            .ok_or(EverlendError::MathOverflow)?;
+        let rewards = u64::try_from(rewards).map_err(|_| EverlendError::MathOverflow)?;
+
+        if self.reward_max_amount_per_period == 0 {
+            return Ok(rewards);
+        }
+
+        let max_rewards = self
+            .reward_max_amount_per_period
+            .checked_mul(num_periods)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(cmp::min(rewards, max_rewards))
+    }
+
+    /// Calculate rewards of fixed ratio vault since the saved indexes, capped per mining
+    pub fn fixed_ratio_rewards(
+        &self,
+        amount: u64,
+        reward_index: &RewardIndex,
+    ) -> Result<u64, ProgramError> {
+        let index = self
+            .index_with_precision
+            .checked_sub(reward_index.index_with_precision)
+            .ok_or(EverlendError::MathOverflow)?;
+        let capped_index = self
+            .capped_index_with_precision
+            .checked_sub(reward_index.capped_index_with_precision)
+            .ok_or(EverlendError::MathOverflow)?;
+        let cap = self
+            .cap_index_with_precision
+            .checked_sub(reward_index.cap_index_with_precision)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        let uncapped_rewards = index
+            .checked_sub(capped_index)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_mul(amount.into())
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(PRECISION)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        let capped_rewards = cmp::min(
+            capped_index
+                .checked_mul(amount.into())
+                .ok_or(EverlendError::MathOverflow)?
+                .checked_div(PRECISION)
+                .ok_or(EverlendError::MathOverflow)?,
+            cap.checked_div(PRECISION)
+                .ok_or(EverlendError::MathOverflow)?,
+        );
+
+        let rewards = uncapped_rewards
+            .checked_add(capped_rewards)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(rewards as u64)
+    }
+
+    /// Change emission parameters, rewards up to now are accumulated with the previous ones
+    pub fn update(&mut self, params: VaultCheckpoint) {
+        self.ratio_base = params.ratio_base;
+        self.ratio_quote = params.ratio_quote;
+        self.reward_period_sec = params.reward_period_sec;
+        self.distribution_starts_at = params.distribution_starts_at;
+        self.distribution_ends_at = params.distribution_ends_at;
+        self.reward_max_amount_per_period = params.reward_max_amount_per_period;
+    }
+
+    /// Calculate rewards of proportional vault since the saved index
+    pub fn proportional_rewards(
+        &self,
+        amount: u64,
+        index_with_precision: u128,
+    ) -> Result<u64, ProgramError> {
+        let rewards = self
+            .index_with_precision
+            .checked_sub(index_with_precision)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_mul(amount.into())
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(PRECISION)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(rewards as u64)
+    }
+
+    /// Accumulate reward per share for the passed time
+    pub fn refresh_index(&mut self, total_amount: u64, timestamp: u64) -> ProgramResult {
+        match self.distribution_mode {
+            DistributionMode::FixedRatio => self.refresh_ratio_index(timestamp),
+            DistributionMode::Proportional => {
+                self.refresh_proportional_index(total_amount, timestamp)
+            }
+        }
+    }
+
+    /// Accumulate fixed ratio reward per share and the cap per mining pro rata by time,
+    /// so changed parameters apply from the update only
+    fn refresh_ratio_index(&mut self, timestamp: u64) -> ProgramResult {
+        let timestamp = self.accrual_timestamp(timestamp);
+
+        let reward_period_start = cmp::max(self.index_updated_at, self.distribution_starts_at);
+        if timestamp <= reward_period_start {
+            return Ok(());
+        }
+        let reward_period = (timestamp - reward_period_start) as u128;
+
+        let index_per_period = (self.ratio_quote as u128)
+            .checked_mul(PRECISION)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(self.ratio_base.into())
+            .ok_or(EverlendError::MathOverflow)?;
+        let index = mul_div(
+            index_per_period,
+            reward_period,
+            self.reward_period_sec.into(),
+        )?;
+
+        self.index_with_precision = self
+            .index_with_precision
+            .checked_add(index)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        if self.reward_max_amount_per_period > 0 {
+            let cap = mul_div(
+                (self.reward_max_amount_per_period as u128) * PRECISION,
+                reward_period,
+                self.reward_period_sec.into(),
+            )?;
+
+            self.capped_index_with_precision = self
+                .capped_index_with_precision
+                .checked_add(index)
+                .ok_or(EverlendError::MathOverflow)?;
+            self.cap_index_with_precision = self
+                .cap_index_with_precision
+                .checked_add(cap)
+                .ok_or(EverlendError::MathOverflow)?;
+        }
+
+        self.index_updated_at = timestamp;
+
+        Ok(())
+    }
+
+    /// Accumulate proportional reward per share pro rata by time.
+    /// Time of the remainder below a whole unit of rewards is carried forward
+    fn refresh_proportional_index(&mut self, total_amount: u64, timestamp: u64) -> ProgramResult {
+        let timestamp = self.accrual_timestamp(timestamp);
+
+        let reward_period_start = cmp::max(self.index_updated_at, self.distribution_starts_at);
+        if timestamp <= reward_period_start {
+            return Ok(());
+        }
+
+        // nobody stakes - nothing to distribute
+        if total_amount == 0 {
+            self.index_updated_at = timestamp;
+            return Ok(());
+        }
+
+        let reward_period = (timestamp - reward_period_start) as u128;
+        let reward_period_sec = self.reward_period_sec as u128;
+        let reward_amount_per_period = self.reward_max_amount_per_period as u128;
+
+        let rewards = mul_div(reward_amount_per_period, reward_period, reward_period_sec)?;
+        if rewards == 0 {
+            return Ok(());
+        }
+        let rewards = u64::try_from(rewards).map_err(|_| EverlendError::MathOverflow)?;
+
+        // distribution stops once the funded budget is exhausted
+        let accrued_rewards = self.accrue(rewards)?;
+
+        let index = (accrued_rewards as u128)
+            .checked_mul(PRECISION)
+            .ok_or(EverlendError::MathOverflow)?
+            .checked_div(total_amount.into())
+            .ok_or(EverlendError::MathOverflow)?;
+
+        self.index_with_precision = self
+            .index_with_precision
+            .checked_add(index)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        // time it takes to emit the rewards, rounded up
+        let distributed_period = (rewards as u128 * reward_period_sec)
+            .checked_add(reward_amount_per_period - 1)
+            .ok_or(EverlendError::MathOverflow)?
+            / reward_amount_per_period;
+
+        self.index_updated_at = reward_period_start
+            .checked_add(distributed_period as u64)
+            .ok_or(EverlendError::MathOverflow)?;
+
+        Ok(())
+    }
+}
+
+/// Calculate `a * b / c` without overflow of the intermediate product
+fn mul_div(a: u128, b: u128, c: u128) -> Result<u128, ProgramError> {
+    let quotient = a.checked_div(c).ok_or(EverlendError::MathOverflow)?;
+    let remainder = a % c;
+
+    quotient
+        .checked_mul(b)
+        .and_then(|result| result.checked_add(remainder.checked_mul(b)? / c))
+        .ok_or_else(|| EverlendError::MathOverflow.into())
+}
+
+/// Emission parameters of reward vault changed at the timestamp
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone)]
+pub struct VaultCheckpoint {
+    /// Time of parameters change
+    pub updated_at: u64,
+    /// Reward ratio of deposit currency
+    pub ratio_base: u64,
+    /// Reward ratio of reward currency
+    pub ratio_quote: u64,
+    /// Time period for reward calculation
+    pub reward_period_sec: u32,
+    /// Timestamp since when distribution begins
+    pub distribution_starts_at: u64,
+    /// Timestamp when distribution ends (unlimited if not set)
+    pub distribution_ends_at: Option<u64>,
+    /// Maximum amount of reward per period (cap)
+    pub reward_max_amount_per_period: u64,
+}
+
+impl VaultCheckpoint {
+    /// LEN
+    pub const LEN: usize = 8 + 8 + 8 + 4 + 8 + 9 + 8;
+}
+
+/// Reward distribution mode
+#[derive(
+    Debug, BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone, Copy, PartialEq, Eq,
+)]
+pub enum DistributionMode {
+    /// Each staker gets `amount * ratio_quote / ratio_base` per period
+    #[default]
+    FixedRatio,
+    /// A fixed amount per period is split between stakers pro rata
+    Proportional,
 }
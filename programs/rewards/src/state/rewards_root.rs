@@ -1,5 +1,8 @@
-use crate::state::AccountType;
+use crate::state::{AccountType, Multisig, RewardsRootV1};
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use everlend_utils::{assert_authority, EverlendError};
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
 use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack, Sealed};
@@ -12,21 +15,143 @@ pub struct RewardsRoot {
     pub account_type: AccountType,
     /// Authority address
     pub authority: Pubkey,
+    /// Proposed authority address waiting for acceptance
+    pub pending_authority: Option<Pubkey>,
+    /// Owner of treasury token accounts receiving early exit penalties
+    pub treasury: Pubkey,
+    /// Account allowed to manage vaults and their funders
+    pub vault_manager: Pubkey,
+    /// Account allowed to pause the root and its pools
+    pub pauser: Pubkey,
+    /// Account allowed to fill vaults of all pools
+    pub funder: Pubkey,
+    /// Operations of all pools are paused
+    pub paused: bool,
+    /// Delay before proposed admin actions can be executed
+    pub timelock_sec: u64,
+    /// Id of the next proposed admin action
+    pub next_action_id: u64,
 }
 
 impl RewardsRoot {
     /// Init root account
     pub fn init(authority: Pubkey) -> RewardsRoot {
         RewardsRoot {
-            account_type: AccountType::RewardsRoot,
+            account_type: AccountType::RewardsRootV2,
             authority,
+            pending_authority: None,
+            treasury: Pubkey::default(),
+            vault_manager: Pubkey::default(),
+            pauser: Pubkey::default(),
+            funder: Pubkey::default(),
+            paused: false,
+            timelock_sec: 0,
+            next_action_id: 0,
         }
     }
+
+    /// Set treasury receiving early exit penalties
+    pub fn set_treasury(&mut self, treasury: Pubkey) {
+        msg!("Treasury changed from {} to {}", self.treasury, treasury);
+
+        self.treasury = treasury;
+    }
+
+    /// Set account of the role
+    pub fn set_role(&mut self, role: Role, account: Pubkey) {
+        let role_account = self.role_mut(role);
+
+        msg!(
+            "{:?} role changed from {} to {}",
+            role,
+            role_account,
+            account
+        );
+
+        *role_account = account;
+    }
+
+    /// Returns account of the role
+    pub fn role_mut(&mut self, role: Role) -> &mut Pubkey {
+        match role {
+            Role::VaultManager => &mut self.vault_manager,
+            Role::Pauser => &mut self.pauser,
+            Role::Funder => &mut self.funder,
+        }
+    }
+
+    /// Check if account has the role, authority has every role
+    pub fn has_role(&self, role: Role, account: &Pubkey) -> bool {
+        let role_account = match role {
+            Role::VaultManager => &self.vault_manager,
+            Role::Pauser => &self.pauser,
+            Role::Funder => &self.funder,
+        };
+
+        account == &self.authority
+            || (*role_account != Pubkey::default() && account == role_account)
+    }
+
+    /// Assert that parameter changes don't have to go through the timelock
+    pub fn assert_not_timelocked(&self) -> ProgramResult {
+        if self.timelock_sec > 0 {
+            return Err(EverlendError::TimelockRequired.into());
+        }
+
+        Ok(())
+    }
+
+    /// Assert that signer is the authority, a multisig authority follows it with co-signers
+    pub fn assert_authority<'a>(
+        &self,
+        program_id: &Pubkey,
+        signer: &AccountInfo<'a>,
+        co_signers: &[AccountInfo<'a>],
+    ) -> ProgramResult {
+        assert_authority::<Multisig>(program_id, &self.authority, signer, co_signers)
+    }
+
+    /// Assert that signer has the role
+    pub fn assert_role<'a>(
+        &self,
+        program_id: &Pubkey,
+        role: Role,
+        signer: &AccountInfo<'a>,
+        co_signers: &[AccountInfo<'a>],
+    ) -> ProgramResult {
+        // multisig account can't hold a role, its threshold would be bypassed
+        if self.has_role(role, signer.key) && signer.owner != program_id {
+            return Ok(());
+        }
+
+        if co_signers.is_empty() {
+            msg!("Account {} has no {:?} role", signer.key, role);
+            return Err(EverlendError::MissingRole.into());
+        }
+
+        self.assert_authority(program_id, signer, co_signers)
+    }
+}
+
+/// Roles delegated by the root authority
+#[derive(Debug, BorshDeserialize, BorshSerialize, BorshSchema, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Adds, updates and removes vaults, manages their funders
+    VaultManager,
+    /// Pauses the root and its pools
+    Pauser,
+    /// Fills vaults of all pools
+    Funder,
 }
 
 impl Sealed for RewardsRoot {}
 impl Pack for RewardsRoot {
-    const LEN: usize = 1 + (32);
+    const LEN: usize = 1 + (32 + (1 + 32) + 32 + 32 + 32 + 32 + 1 + 8 + 8);
+
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        // deprecated layouts are shorter
+        Self::unpack_from_slice(input)
+    }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut slice = dst;
@@ -35,7 +160,13 @@ impl Pack for RewardsRoot {
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let mut src_mut = src;
-        Self::deserialize(&mut src_mut).map_err(|_| {
+        match AccountType::peek(src)? {
+            AccountType::RewardsRootV1 => {
+                RewardsRootV1::deserialize(&mut src_mut).map(RewardsRoot::from)
+            }
+            _ => Self::deserialize(&mut src_mut),
+        }
+        .map_err(|_| {
             msg!("Failed to deserialize");
             msg!("Actual LEN: {}", std::mem::size_of::<RewardsRoot>());
             ProgramError::InvalidAccountData
@@ -45,6 +176,6 @@ impl Pack for RewardsRoot {
 
 impl IsInitialized for RewardsRoot {
     fn is_initialized(&self) -> bool {
-        self.account_type == AccountType::RewardsRoot
+        self.account_type == AccountType::RewardsRootV2
     }
 }
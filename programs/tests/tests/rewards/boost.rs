@@ -0,0 +1,189 @@
+use crate::utils::*;
+use everlend_rewards::state::{
+    DistributionMode, InitRewardPoolParams, Mining, RewardPool, RewardVault, MAX_BOOST_LOCK_SEC,
+};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let liquidity_mint = Keypair::new();
+    let deposit_amount = 1000;
+
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            deposit_amount * 2,
+        )
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining_with_lock(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+            MAX_BOOST_LOCK_SEC / 2,
+        )
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        &get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.amount, deposit_amount);
+    assert_eq!(mining.weighted_amount, deposit_amount * 5 / 2);
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(
+        reward_pool_account.total_weighted_amount,
+        deposit_amount * 5 / 2
+    );
+
+    // boosted stake is locked for the lock period
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+            false,
+        )
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn boosted_reward_calculation() {
+    let timestamp = 1_000_000;
+
+    let mut reward_pool = RewardPool::init(InitRewardPoolParams {
+        rewards_root: Pubkey::new_unique(),
+        bump: 0,
+        liquidity_mint: Pubkey::new_unique(),
+        lock_time_sec: 0,
+    });
+
+    reward_pool
+        .add_vault(RewardVault {
+            reward_mint: Pubkey::new_unique(),
+            reward_period_sec: 60,
+            distribution_starts_at: timestamp,
+            reward_max_amount_per_period: 1_000,
+            distribution_mode: DistributionMode::Proportional,
+            index_updated_at: timestamp,
+            total_funded: u64::MAX,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut boosted = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+    let mut regular = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+
+    reward_pool
+        .deposit(&mut boosted, 1_000, MAX_BOOST_LOCK_SEC, timestamp)
+        .unwrap();
+    reward_pool
+        .deposit(&mut regular, 1_000, 0, timestamp)
+        .unwrap();
+
+    assert_eq!(boosted.weighted_amount, 4_000);
+    assert_eq!(regular.weighted_amount, 1_000);
+    assert_eq!(reward_pool.total_weighted_amount, 5_000);
+
+    reward_pool
+        .refresh_mining(&mut boosted, timestamp + 60)
+        .unwrap();
+    reward_pool
+        .refresh_mining(&mut regular, timestamp + 60)
+        .unwrap();
+
+    assert_eq!(boosted.indexes[0].rewards, 800);
+    assert_eq!(regular.indexes[0].rewards, 200);
+
+    // boost ends with the lock period
+    reward_pool
+        .refresh_mining(&mut boosted, timestamp + MAX_BOOST_LOCK_SEC)
+        .unwrap();
+
+    assert_eq!(boosted.weighted_amount, 1_000);
+    assert_eq!(reward_pool.total_weighted_amount, 2_000);
+}
+
+#[tokio::test]
+async fn expired_boost_stops_earning() {
+    let timestamp = 1_000_000;
+
+    let mut reward_pool = RewardPool::init(InitRewardPoolParams {
+        rewards_root: Pubkey::new_unique(),
+        bump: 0,
+        liquidity_mint: Pubkey::new_unique(),
+        lock_time_sec: 0,
+    });
+
+    reward_pool
+        .add_vault(RewardVault {
+            reward_mint: Pubkey::new_unique(),
+            reward_period_sec: 60,
+            distribution_starts_at: timestamp,
+            reward_max_amount_per_period: 1_000,
+            distribution_mode: DistributionMode::Proportional,
+            index_updated_at: timestamp,
+            total_funded: u64::MAX,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut boosted = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+    let mut regular = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+
+    reward_pool
+        .deposit(&mut boosted, 1_000, MAX_BOOST_LOCK_SEC, timestamp)
+        .unwrap();
+    reward_pool
+        .deposit(&mut regular, 1_000, 0, timestamp)
+        .unwrap();
+
+    // boosted mining isn't touched until long after the unlock
+    let new_timestamp = timestamp + MAX_BOOST_LOCK_SEC * 2;
+    let periods = MAX_BOOST_LOCK_SEC * 2 / 60;
+
+    reward_pool
+        .refresh_mining(&mut boosted, new_timestamp)
+        .unwrap();
+    reward_pool
+        .refresh_mining(&mut regular, new_timestamp)
+        .unwrap();
+
+    // boost counts for half of the time: (4000 + 1000) / 2 of 5000
+    assert_eq!(boosted.indexes[0].rewards, periods * 500);
+    assert_eq!(regular.indexes[0].rewards, periods * 200);
+    assert_eq!(boosted.weighted_amount, 1_000);
+    assert_eq!(reward_pool.total_weighted_amount, 2_000);
+
+    // share of the expired boost isn't accrued
+    assert_eq!(reward_pool.vaults[0].total_accrued, periods * 700);
+}
@@ -1,6 +1,8 @@
 use crate::utils::*;
-use everlend_rewards::state::{Mining, RewardPool, RewardVault};
-use solana_program::program_pack::Pack;
+use everlend_rewards::state::{
+    DistributionMode, InitRewardPoolParams, Mining, RewardPool, RewardVault,
+};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
 use solana_program_test::*;
 use solana_sdk::sysvar::clock;
 use solana_sdk::{signature::Keypair, signer::Signer};
@@ -58,6 +60,11 @@ async fn success() {
         )
         .await;
 
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
     test_reward_pool
         .fill_vault(
             &mut context,
@@ -138,6 +145,123 @@ async fn success() {
     println!("vault: {:?}", vault_acc);
 }
 
+#[tokio::test]
+async fn success_proportional() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    let reward_per_period = 1_000;
+    let vault = test_reward_pool
+        .add_proportional_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_period,
+            clock.unix_timestamp as u64,
+            reward_per_period,
+        )
+        .await;
+
+    let reward_amount = 1_000_000;
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            reward_amount,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await
+        .unwrap();
+
+    // deposit tokens: shares 3/4 and 1/4
+    let mut users = vec![];
+    for deposit_amount in [30_000, 10_000] {
+        let token_holder = test_reward_pool
+            .create_token_holder(
+                &mut context,
+                &pool_mint.pubkey(),
+                10_000_000_000,
+                deposit_amount,
+            )
+            .await;
+
+        test_reward_pool
+            .deposit_mining(
+                &mut context,
+                &pool_mint.pubkey(),
+                &token_holder.token_account,
+                &token_holder.owner,
+                deposit_amount,
+            )
+            .await
+            .unwrap();
+
+        users.push(token_holder);
+    }
+
+    // update solana clock
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    for (token_holder, exp_reward_amount) in users.iter().zip([750, 250]) {
+        let user_reward_account = Keypair::new();
+
+        test_reward_pool
+            .claim(
+                &mut context,
+                &token_holder.owner,
+                &pool_mint.pubkey(),
+                &reward_mint.pubkey(),
+                &user_reward_account,
+            )
+            .await
+            .unwrap();
+
+        let user_reward = Account::unpack(
+            get_account(&mut context, &user_reward_account.pubkey())
+                .await
+                .data
+                .borrow(),
+        )
+        .unwrap();
+
+        assert_eq!(user_reward.amount, exp_reward_amount);
+    }
+
+    let vault_acc = Account::unpack(get_account(&mut context, &vault).await.data.borrow()).unwrap();
+    assert_eq!(vault_acc.amount, reward_amount - reward_per_period);
+}
+
 #[tokio::test]
 async fn reward_calculation() {
     let base = 100_000_000;
@@ -148,7 +272,7 @@ async fn reward_calculation() {
     check_mining_maths(base, quote, period, period, 20, 100_000_000, 20);
     check_mining_maths(base, quote, period, period * 10, 20, 10_000_000_000, 200);
 
-    check_mining_maths(base, quote, period, period / 2, 0, 100_000_000, 0);
+    check_mining_maths(base, quote, period, period / 2, 0, 100_000_000, 500);
     check_mining_maths(base, quote, period, period * 5, 0, 25_000_000, 1250);
 
     let base = 1_000;
@@ -176,7 +300,7 @@ fn check_mining_maths(
         .expect("Time went backwards")
         .as_secs();
 
-    let vault = RewardVault {
+    let mut vault = RewardVault {
         bump: 0,
         reward_mint: Keypair::new().pubkey(),
         ratio_base: base,
@@ -184,17 +308,517 @@ fn check_mining_maths(
         reward_period_sec: period,
         distribution_starts_at: current_timestamp,
         reward_max_amount_per_period: max_amount,
+        total_funded: u64::MAX,
+        ..Default::default()
     };
 
     let mut mining = Mining::initialize(reward_pool.pubkey(), 0, owner.pubkey());
     mining.amount = deposit;
+    mining.weighted_amount = deposit;
     mining.rewards_calculated_at = current_timestamp;
 
     let new_timestamp = current_timestamp + add_time as u64;
+    vault.refresh_index(deposit, new_timestamp).unwrap();
     mining
-        .refresh_rewards(vec![vault].iter(), new_timestamp)
+        .refresh_rewards(vec![vault].iter_mut(), new_timestamp)
         .unwrap();
 
     assert_eq!(mining.indexes[0].rewards, reward);
     assert_eq!(mining.rewards_calculated_at, new_timestamp);
 }
+
+#[tokio::test]
+async fn proportional_reward_calculation() {
+    let period = 60_u32;
+
+    check_proportional_maths(period, period, 1_000, &[3_000, 1_000], &[750, 250]);
+    check_proportional_maths(period, period * 3, 1_000, &[1, 1, 1], &[1000, 1000, 1000]);
+    check_proportional_maths(period, period / 2, 1_000, &[1_000], &[500]);
+    check_proportional_maths(period, period * 2, 10, &[1_000_000, 3], &[19, 0]);
+}
+
+fn check_proportional_maths(
+    period: u32,
+    add_time: u32,
+    reward_per_period: u64,
+    deposits: &[u64],
+    rewards: &[u64],
+) {
+    let reward_pool = Keypair::new();
+
+    let current_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs();
+
+    let mut vault = RewardVault {
+        reward_mint: Keypair::new().pubkey(),
+        reward_period_sec: period,
+        distribution_starts_at: current_timestamp,
+        reward_max_amount_per_period: reward_per_period,
+        distribution_mode: DistributionMode::Proportional,
+        index_updated_at: current_timestamp,
+        total_funded: u64::MAX,
+        ..Default::default()
+    };
+
+    let mut minings: Vec<Mining> = deposits
+        .iter()
+        .map(|deposit| {
+            let mut mining = Mining::initialize(reward_pool.pubkey(), 0, Keypair::new().pubkey());
+            mining
+                .refresh_rewards(vec![vault.clone()].iter_mut(), current_timestamp)
+                .unwrap();
+            mining.amount = *deposit;
+            mining.weighted_amount = *deposit;
+            mining
+        })
+        .collect();
+
+    let new_timestamp = current_timestamp + add_time as u64;
+    vault
+        .refresh_index(deposits.iter().sum(), new_timestamp)
+        .unwrap();
+
+    for (mining, reward) in minings.iter_mut().zip(rewards) {
+        mining
+            .refresh_rewards(vec![vault.clone()].iter_mut(), new_timestamp)
+            .unwrap();
+
+        assert_eq!(mining.indexes[0].rewards, *reward);
+        assert_eq!(
+            mining.indexes[0].index_with_precision,
+            vault.index_with_precision
+        );
+    }
+}
+
+#[tokio::test]
+async fn claim_all() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+    let reward_period = 3600;
+    let reward_amount = 1_000_000;
+
+    let mut vaults = vec![];
+    for ratio_base in [100, 50] {
+        let reward_mint = Keypair::new();
+        create_mint(&mut context, &reward_mint).await.unwrap();
+
+        let vault = test_reward_pool
+            .add_vault(
+                &mut context,
+                &pool_mint.pubkey(),
+                &reward_mint.pubkey(),
+                ratio_base,
+                1,
+                reward_period,
+                clock.unix_timestamp as u64,
+            )
+            .await;
+
+        let rewarder = test_reward_pool
+            .create_token_holder(
+                &mut context,
+                &reward_mint.pubkey(),
+                10_000_000_000,
+                reward_amount,
+            )
+            .await;
+
+        test_reward_pool
+            .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+            .await
+            .unwrap();
+
+        test_reward_pool
+            .fill_vault(
+                &mut context,
+                &rewarder,
+                &pool_mint.pubkey(),
+                &reward_mint.pubkey(),
+                reward_amount,
+            )
+            .await
+            .unwrap();
+
+        vaults.push((reward_mint, vault));
+    }
+
+    // deposit tokens
+    let deposit_amount = 50_000;
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &pool_mint.pubkey(),
+            10_000_000_000,
+            deposit_amount,
+        )
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    // update solana clock
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    let mut rewards = vec![];
+    for (reward_mint, _) in vaults.iter() {
+        let user_reward_account = Keypair::new();
+        create_token_account(
+            &mut context,
+            &user_reward_account,
+            &reward_mint.pubkey(),
+            &token_holder.owner.pubkey(),
+            0,
+        )
+        .await
+        .unwrap();
+
+        rewards.push((reward_mint.pubkey(), user_reward_account.pubkey()));
+    }
+
+    test_reward_pool
+        .claim_all(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &rewards,
+        )
+        .await
+        .unwrap();
+
+    let exp_reward_amounts = [500, 1000];
+    for (i, (_, user_reward_account)) in rewards.iter().enumerate() {
+        let user_reward = get_token_balance(&mut context, user_reward_account).await;
+        assert_eq!(user_reward, exp_reward_amounts[i]);
+
+        let vault = get_token_balance(&mut context, &vaults[i].1).await;
+        assert_eq!(vault, reward_amount - exp_reward_amounts[i]);
+    }
+
+    let mining = Mining::unpack(
+        &get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+
+    assert!(mining.indexes.iter().all(|index| index.rewards == 0));
+}
+
+#[tokio::test]
+async fn underfunded_vault() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+
+    let (reward_pool_pubkey, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    let vault = test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            reward_period,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(&mut context, &reward_mint.pubkey(), 10_000_000_000, 1_300)
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    // fund less than a single period needs
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            300,
+        )
+        .await
+        .unwrap();
+
+    let deposit_amount = 50_000;
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &pool_mint.pubkey(),
+            10_000_000_000,
+            deposit_amount,
+        )
+        .await;
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    for (slot, fill_amount, exp_reward_amount, exp_vault_amount) in
+        [(10, 1_000, 300, 1_000), (20, 0, 500, 500)]
+    {
+        // update solana clock
+        clock.unix_timestamp += reward_period as i64;
+        clock_account.data = bincode::serialize(&clock).unwrap();
+        context.set_account(&clock::id(), &clock_account.clone().into());
+        context.warp_to_slot(slot).unwrap();
+
+        let user_reward_account = Keypair::new();
+        test_reward_pool
+            .claim(
+                &mut context,
+                &token_holder.owner,
+                &pool_mint.pubkey(),
+                &reward_mint.pubkey(),
+                &user_reward_account,
+            )
+            .await
+            .unwrap();
+
+        let user_reward = get_token_balance(&mut context, &user_reward_account.pubkey()).await;
+        assert_eq!(user_reward, exp_reward_amount);
+
+        if fill_amount > 0 {
+            test_reward_pool
+                .fill_vault(
+                    &mut context,
+                    &rewarder,
+                    &pool_mint.pubkey(),
+                    &reward_mint.pubkey(),
+                    fill_amount,
+                )
+                .await
+                .unwrap();
+        }
+
+        let vault_amount = get_token_balance(&mut context, &vault).await;
+        assert_eq!(vault_amount, exp_vault_amount);
+    }
+
+    let reward_pool = RewardPool::unpack(
+        &get_account(&mut context, &reward_pool_pubkey)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+
+    assert_eq!(reward_pool.vaults[0].total_funded, 1_300);
+    assert_eq!(reward_pool.vaults[0].total_accrued, 800);
+    assert_eq!(reward_pool.vaults[0].total_claimed, 800);
+    assert_eq!(reward_pool.vaults[0].liabilities(), 0);
+}
+
+#[tokio::test]
+async fn distribution_end_calculation() {
+    let period = 60_u32;
+    let current_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs();
+    let distribution_ends_at = current_timestamp + period as u64 * 2;
+    let new_timestamp = current_timestamp + period as u64 * 10;
+
+    // fixed ratio
+    let mut vaults = vec![RewardVault {
+        reward_mint: Keypair::new().pubkey(),
+        ratio_base: 100,
+        ratio_quote: 1,
+        reward_period_sec: period,
+        distribution_starts_at: current_timestamp,
+        distribution_ends_at: Some(distribution_ends_at),
+        total_funded: u64::MAX,
+        ..Default::default()
+    }];
+
+    let mut mining = Mining::initialize(Keypair::new().pubkey(), 0, Keypair::new().pubkey());
+    mining.amount = 50_000;
+    mining.weighted_amount = 50_000;
+    mining.rewards_calculated_at = current_timestamp;
+    vaults[0]
+        .refresh_index(mining.amount, new_timestamp)
+        .unwrap();
+    mining
+        .refresh_rewards(vaults.iter_mut(), new_timestamp)
+        .unwrap();
+
+    assert_eq!(mining.indexes[0].rewards, 1_000);
+
+    // proportional
+    let mut vaults = vec![RewardVault {
+        reward_mint: Keypair::new().pubkey(),
+        reward_period_sec: period,
+        distribution_starts_at: current_timestamp,
+        distribution_ends_at: Some(distribution_ends_at),
+        reward_max_amount_per_period: 1_000,
+        distribution_mode: DistributionMode::Proportional,
+        index_updated_at: current_timestamp,
+        total_funded: u64::MAX,
+        ..Default::default()
+    }];
+
+    let mut mining = Mining::initialize(Keypair::new().pubkey(), 0, Keypair::new().pubkey());
+    mining
+        .refresh_rewards(vaults.iter_mut(), current_timestamp)
+        .unwrap();
+    mining.amount = 1_000;
+    mining.weighted_amount = 1_000;
+
+    vaults[0]
+        .refresh_index(mining.amount, new_timestamp)
+        .unwrap();
+    mining
+        .refresh_rewards(vaults.iter_mut(), new_timestamp)
+        .unwrap();
+
+    assert_eq!(mining.indexes[0].rewards, 2_000);
+}
+
+#[tokio::test]
+async fn partial_periods() {
+    let period = 60_u64;
+    let current_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs();
+
+    // frequent refreshes don't earn more than a single one, losing at most a unit to rounding
+    for refreshes in [vec![120], vec![30, 90, 100, 120]] {
+        let refreshes_count = refreshes.len() as u64;
+        let mut vaults = vec![RewardVault {
+            reward_mint: Keypair::new().pubkey(),
+            ratio_base: 1_000,
+            ratio_quote: 1,
+            reward_period_sec: period as u32,
+            distribution_starts_at: current_timestamp,
+            total_funded: u64::MAX,
+            ..Default::default()
+        }];
+
+        let mut mining = Mining::initialize(Keypair::new().pubkey(), 0, Keypair::new().pubkey());
+        mining.amount = 60_000;
+        mining.weighted_amount = 60_000;
+        mining.rewards_calculated_at = current_timestamp;
+
+        for add_time in refreshes {
+            let timestamp = current_timestamp + add_time;
+            vaults[0].refresh_index(mining.amount, timestamp).unwrap();
+            mining
+                .refresh_rewards(vaults.iter_mut(), timestamp)
+                .unwrap();
+        }
+
+        let rewards = mining.indexes[0].rewards;
+        assert!(rewards <= 120 && rewards + refreshes_count > 120);
+    }
+}
+
+#[tokio::test]
+async fn deposit_before_period_end() {
+    let timestamp = 1_000_000;
+    let period = 3600;
+
+    let mut reward_pool = RewardPool::init(InitRewardPoolParams {
+        rewards_root: Pubkey::new_unique(),
+        bump: 0,
+        liquidity_mint: Pubkey::new_unique(),
+        lock_time_sec: 0,
+    });
+
+    // both vaults emit a unit per second
+    reward_pool
+        .add_vault(RewardVault {
+            reward_mint: Pubkey::new_unique(),
+            reward_period_sec: period,
+            distribution_starts_at: timestamp,
+            reward_max_amount_per_period: period as u64,
+            distribution_mode: DistributionMode::Proportional,
+            index_updated_at: timestamp,
+            total_funded: u64::MAX,
+            ..Default::default()
+        })
+        .unwrap();
+    reward_pool
+        .add_vault(RewardVault {
+            reward_mint: Pubkey::new_unique(),
+            ratio_base: 1_000,
+            ratio_quote: period as u64,
+            reward_period_sec: period,
+            distribution_starts_at: timestamp,
+            index_updated_at: timestamp,
+            total_funded: u64::MAX,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut staker = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+    let mut late = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+
+    reward_pool
+        .deposit(&mut staker, 1_000, 0, timestamp)
+        .unwrap();
+
+    // staked for two seconds around the end of the period
+    let period_end = timestamp + period as u64;
+    reward_pool
+        .deposit(&mut late, 1_000, 0, period_end - 1)
+        .unwrap();
+    reward_pool
+        .withdraw(&mut late, 1_000, period_end + 1)
+        .unwrap();
+    reward_pool
+        .refresh_mining(&mut staker, period_end + 1)
+        .unwrap();
+
+    assert_eq!(late.indexes[0].rewards, 1);
+    assert_eq!(late.indexes[1].rewards, 2);
+    assert_eq!(staker.indexes[0].rewards, 3_600);
+    assert_eq!(staker.indexes[1].rewards, 3_601);
+}
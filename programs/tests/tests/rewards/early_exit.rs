@@ -0,0 +1,324 @@
+use crate::utils::*;
+use everlend_rewards::state::{Mining, PenaltyDestination, RewardPool, DEFAULT_MAX_VAULTS};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+const LOCK_TIME_SEC: u64 = 3600;
+const PENALTY_BPS: u16 = 1000;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let liquidity_mint = Keypair::new();
+    let initial_balance = 10_000;
+    let deposit_amount = 1000;
+    let withdraw_amount = 500;
+    let penalty = 50;
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, LOCK_TIME_SEC)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .update_pool(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            LOCK_TIME_SEC,
+            0,
+            PENALTY_BPS,
+            PenaltyDestination::Treasury,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .unwrap();
+
+    let treasury = Keypair::new();
+    let treasury_token_account = Keypair::new();
+    create_token_account(
+        &mut context,
+        &treasury_token_account,
+        &liquidity_mint.pubkey(),
+        &treasury.pubkey(),
+        0,
+    )
+    .await
+    .unwrap();
+
+    test_reward_pool
+        .set_treasury(&mut context, &treasury.pubkey())
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            initial_balance,
+        )
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    // treasury account is required
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            withdraw_amount,
+            false,
+        )
+        .await
+        .unwrap_err();
+
+    test_reward_pool
+        .withdraw_mining_with_treasury(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            withdraw_amount,
+            false,
+            Some(&treasury_token_account.pubkey()),
+        )
+        .await
+        .unwrap();
+
+    let token_balance = get_token_balance(&mut context, &token_holder.token_account).await;
+    assert_eq!(
+        token_balance,
+        initial_balance - deposit_amount + withdraw_amount - penalty
+    );
+
+    let treasury_balance = get_token_balance(&mut context, &treasury_token_account.pubkey()).await;
+    assert_eq!(treasury_balance, penalty);
+
+    let mining = Mining::unpack(
+        &get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.amount, deposit_amount - withdraw_amount);
+    assert_eq!(mining.locks[0].amount, deposit_amount - withdraw_amount);
+}
+
+#[tokio::test]
+async fn stakers() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let liquidity_mint = Keypair::new();
+    let initial_balance = 10_000;
+    let deposit_amount = 1000;
+    let penalty = 100;
+
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, LOCK_TIME_SEC)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .update_pool(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            LOCK_TIME_SEC,
+            0,
+            PENALTY_BPS,
+            PenaltyDestination::Stakers,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .unwrap();
+
+    let mut users = vec![];
+    for _ in 0..2 {
+        let token_holder = test_reward_pool
+            .create_token_holder(
+                &mut context,
+                &liquidity_mint.pubkey(),
+                10_000_000_000,
+                initial_balance,
+            )
+            .await;
+
+        test_reward_pool
+            .deposit_mining(
+                &mut context,
+                &liquidity_mint.pubkey(),
+                &token_holder.token_account,
+                &token_holder.owner,
+                deposit_amount,
+            )
+            .await
+            .unwrap();
+
+        users.push(token_holder);
+    }
+
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &users[0].token_account,
+            &users[0].owner,
+            deposit_amount,
+            false,
+        )
+        .await
+        .unwrap();
+
+    let token_balance = get_token_balance(&mut context, &users[0].token_account).await;
+    assert_eq!(token_balance, initial_balance - penalty);
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool_account.total_amount, deposit_amount + penalty);
+
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+    clock.unix_timestamp += LOCK_TIME_SEC as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(3).unwrap();
+
+    // penalty is compounded into the stake of remaining user
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &users[1].token_account,
+            &users[1].owner,
+            deposit_amount + penalty,
+            false,
+        )
+        .await
+        .unwrap();
+
+    let token_balance = get_token_balance(&mut context, &users[1].token_account).await;
+    assert_eq!(token_balance, initial_balance + penalty);
+}
+
+#[tokio::test]
+async fn stakers_without_others() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let liquidity_mint = Keypair::new();
+    let initial_balance = 10_000;
+    let deposit_amount = 1000;
+    let penalty = 100;
+
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, LOCK_TIME_SEC)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .update_pool(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            LOCK_TIME_SEC,
+            0,
+            PENALTY_BPS,
+            PenaltyDestination::Stakers,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .unwrap();
+
+    let treasury = Keypair::new();
+    let treasury_token_account = Keypair::new();
+    create_token_account(
+        &mut context,
+        &treasury_token_account,
+        &liquidity_mint.pubkey(),
+        &treasury.pubkey(),
+        0,
+    )
+    .await
+    .unwrap();
+
+    test_reward_pool
+        .set_treasury(&mut context, &treasury.pubkey())
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            initial_balance,
+        )
+        .await;
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    // penalty isn't waived without stakers to share it with
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+            false,
+        )
+        .await
+        .unwrap_err();
+
+    test_reward_pool
+        .withdraw_mining_with_treasury(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+            false,
+            Some(&treasury_token_account.pubkey()),
+        )
+        .await
+        .unwrap();
+
+    let token_balance = get_token_balance(&mut context, &token_holder.token_account).await;
+    assert_eq!(token_balance, initial_balance - penalty);
+
+    let treasury_balance = get_token_balance(&mut context, &treasury_token_account.pubkey()).await;
+    assert_eq!(treasury_balance, penalty);
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool_account.total_amount, 0);
+}
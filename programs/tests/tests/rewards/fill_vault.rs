@@ -1,4 +1,5 @@
 use crate::utils::*;
+use everlend_rewards::state::RewardPool;
 use solana_program::program_pack::Pack;
 use solana_program_test::*;
 use solana_sdk::signature::Keypair;
@@ -48,6 +49,11 @@ async fn success() {
         )
         .await;
 
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
     test_reward_pool
         .fill_vault(
             &mut context,
@@ -68,3 +74,89 @@ async fn success() {
     assert_eq!(vault.amount, initial_balance / 2);
     assert_eq!(rewarder.amount, initial_balance / 2);
 }
+
+#[tokio::test]
+async fn unauthorized_funder() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+
+    let (reward_pool_pubkey, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+
+    let (clock, _) = get_clock(&mut context).await;
+
+    let vault = test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1,
+            1,
+            60,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let initial_balance = 1_000_000;
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            initial_balance,
+        )
+        .await;
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            initial_balance / 2,
+        )
+        .await
+        .unwrap_err();
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    let reward_pool = RewardPool::unpack(
+        &get_account(&mut context, &reward_pool_pubkey)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(reward_pool.funders, vec![rewarder.owner.pubkey()]);
+
+    test_reward_pool
+        .remove_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            initial_balance / 2,
+        )
+        .await
+        .unwrap_err();
+
+    let vault_account = get_account(&mut context, &vault).await;
+    let vault = Account::unpack(vault_account.data.borrow()).unwrap();
+    assert_eq!(vault.amount, 0);
+}
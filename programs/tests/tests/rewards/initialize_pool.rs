@@ -1,5 +1,8 @@
 use crate::utils::*;
-use everlend_rewards::state::RewardPool;
+use everlend_rewards::state::{
+    LockTranche, Mining, RewardIndex, RewardPool, RewardVault, MAX_FUNDERS, MAX_LOCK_TRANCHES,
+    MAX_REWARDS,
+};
 use everlend_utils::find_program_address;
 use solana_program::program_pack::Pack;
 use solana_program_test::*;
@@ -43,3 +46,30 @@ async fn success() {
     assert_eq!(reward_pool_authority, reward_pool_spl.owner);
     assert_eq!(0, reward_pool_spl.amount);
 }
+
+#[test]
+fn full_accounts_fit() {
+    let reward_pool = RewardPool {
+        vaults: vec![
+            RewardVault {
+                distribution_ends_at: Some(0),
+                ..Default::default()
+            };
+            MAX_REWARDS
+        ],
+        funders: vec![Default::default(); MAX_FUNDERS],
+        deposit_authority: Some(Default::default()),
+        receipt_mint: Some(Default::default()),
+        ..Default::default()
+    };
+    let mut data = vec![0; RewardPool::size(MAX_REWARDS)];
+    RewardPool::pack(reward_pool, &mut data).unwrap();
+
+    let mining = Mining {
+        locks: vec![LockTranche::default(); MAX_LOCK_TRANCHES],
+        indexes: vec![RewardIndex::default(); MAX_REWARDS],
+        ..Default::default()
+    };
+    let mut data = vec![0; Mining::size(MAX_REWARDS)];
+    Mining::pack(mining, &mut data).unwrap();
+}
@@ -0,0 +1,330 @@
+use crate::utils::*;
+use everlend_rewards::instruction::RewardsInstruction;
+use everlend_rewards::state::{DistributionMode, Mining, RewardPool};
+use everlend_rewards::{find_mining_program_address, find_vault_program_address};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::transaction::Transaction;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[test]
+fn encodings() {
+    let add_vault = borsh::to_vec(&RewardsInstruction::AddVault {
+        ratio_base: 1,
+        ratio_quote: 2,
+        reward_period_sec: 3,
+        distribution_starts_at: 4,
+        reward_max_amount_per_period: 5,
+    })
+    .unwrap();
+    assert_eq!(add_vault.len(), 1 + 8 + 8 + 4 + 8 + 8);
+    assert_eq!(add_vault[0], 1);
+
+    assert_eq!(
+        borsh::to_vec(&RewardsInstruction::FillVault { amount: 1 }).unwrap(),
+        vec![2, 1, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        borsh::to_vec(&RewardsInstruction::DepositMining { amount: 1 }).unwrap(),
+        vec![3, 1, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        borsh::to_vec(&RewardsInstruction::WithdrawMining).unwrap(),
+        vec![4]
+    );
+    assert_eq!(borsh::to_vec(&RewardsInstruction::Claim).unwrap(), vec![5]);
+    assert_eq!(
+        borsh::to_vec(&RewardsInstruction::InitializeRoot).unwrap(),
+        vec![6]
+    );
+    assert_eq!(
+        borsh::to_vec(&RewardsInstruction::MigratePool).unwrap(),
+        vec![7]
+    );
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw_mining() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+    let liquidity_mint = Keypair::new();
+    let (reward_pool, reward_pool_spl) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &liquidity_mint.pubkey(), 10_000_000_000, 1000)
+        .await;
+
+    let (mining_account, _) = find_mining_program_address(
+        &everlend_rewards::id(),
+        &token_holder.owner.pubkey(),
+        &reward_pool,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[everlend_rewards::instruction::deposit_mining(
+            &everlend_rewards::id(),
+            &reward_pool,
+            &reward_pool_spl,
+            &liquidity_mint.pubkey(),
+            &mining_account,
+            &token_holder.token_account,
+            &token_holder.owner.pubkey(),
+            1000,
+        )],
+        Some(&token_holder.owner.pubkey()),
+        &[&token_holder.owner],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let mining = Mining::unpack(
+        get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.amount, 1000);
+    assert_eq!(mining.weighted_amount, 1000);
+
+    // the whole amount is withdrawn
+    test_reward_pool
+        .withdraw_mining_legacy(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+        )
+        .await
+        .unwrap();
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool_account.total_amount, 0);
+    assert_eq!(
+        get_token_balance(&mut context, &token_holder.token_account).await,
+        1000
+    );
+    assert_eq!(get_token_balance(&mut context, &reward_pool_spl).await, 0);
+    assert!(context
+        .banks_client
+        .get_account(mining_account)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn claim() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+    let liquidity_mint = Keypair::new();
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (vault, _) =
+        find_vault_program_address(&everlend_rewards::id(), &reward_pool, &reward_mint.pubkey());
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[everlend_rewards::instruction::add_vault(
+            &everlend_rewards::id(),
+            &test_reward_pool.rewards_root.pubkey(),
+            &reward_pool,
+            &reward_mint.pubkey(),
+            &vault,
+            &test_reward_pool.root_authority.pubkey(),
+            100,
+            1,
+            3600,
+            clock.unix_timestamp as u64,
+            0,
+        )],
+        Some(&test_reward_pool.root_authority.pubkey()),
+        &[&test_reward_pool.root_authority],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let pool =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(
+        pool.vaults[0].distribution_mode,
+        DistributionMode::FixedRatio
+    );
+    assert_eq!(pool.vaults[0].distribution_ends_at, None);
+
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            1_000_000,
+        )
+        .await;
+    test_reward_pool
+        .add_funder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &rewarder.owner.pubkey(),
+        )
+        .await
+        .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[everlend_rewards::instruction::fill_vault(
+            &everlend_rewards::id(),
+            &reward_pool,
+            &reward_mint.pubkey(),
+            &vault,
+            &rewarder.token_account,
+            &rewarder.owner.pubkey(),
+            1_000_000,
+        )],
+        Some(&rewarder.owner.pubkey()),
+        &[&rewarder.owner],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, &vault).await, 1_000_000);
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            50_000,
+        )
+        .await;
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += 3600;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(3).unwrap();
+
+    let (mining, _) = find_mining_program_address(
+        &everlend_rewards::id(),
+        &token_holder.owner.pubkey(),
+        &reward_pool,
+    );
+    let user_reward_account = Keypair::new();
+    let tx = Transaction::new_signed_with_payer(
+        &[everlend_rewards::instruction::claim(
+            &everlend_rewards::id(),
+            &reward_pool,
+            &reward_mint.pubkey(),
+            &vault,
+            &mining,
+            &token_holder.owner.pubkey(),
+            &user_reward_account.pubkey(),
+        )],
+        Some(&token_holder.owner.pubkey()),
+        &[&token_holder.owner, &user_reward_account],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &user_reward_account.pubkey()).await,
+        500
+    );
+}
+
+#[tokio::test]
+async fn root_paused() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+    let liquidity_mint = Keypair::new();
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &liquidity_mint.pubkey(), 10_000_000_000, 1000)
+        .await;
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            1000,
+        )
+        .await
+        .unwrap();
+
+    let authority = Keypair::from_bytes(&test_reward_pool.root_authority.to_bytes()).unwrap();
+    test_reward_pool
+        .set_root_paused(&mut context, &authority, true)
+        .await
+        .unwrap();
+    test_reward_pool
+        .sync_root_paused(&mut context, &liquidity_mint.pubkey())
+        .await
+        .unwrap();
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert!(reward_pool_account.root_paused);
+
+    // legacy accounts without the root don't bypass its pause
+    test_reward_pool
+        .withdraw_mining_legacy(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+        )
+        .await
+        .unwrap_err();
+
+    test_reward_pool
+        .set_root_paused(&mut context, &authority, false)
+        .await
+        .unwrap();
+    context.warp_to_slot(3).unwrap();
+    test_reward_pool
+        .sync_root_paused(&mut context, &liquidity_mint.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .withdraw_mining_legacy(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &token_holder.token_account).await,
+        1000
+    );
+}
@@ -0,0 +1,243 @@
+use crate::utils::*;
+use everlend_rewards::state::{Mining, RewardPool};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use spl_token::state::Account;
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    let (reward_pool_pubkey, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let deposit_authority = Keypair::new();
+    test_reward_pool
+        .set_deposit_authority(
+            &mut context,
+            &pool_mint.pubkey(),
+            Some(deposit_authority.pubkey()),
+        )
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            reward_period,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let reward_amount = 1_000_000;
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            reward_amount,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await
+        .unwrap();
+
+    // balance is recorded without any token transfer
+    let user = Keypair::new();
+    transfer_sol(&mut context, &user.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+
+    let mining_account = test_reward_pool
+        .managed_deposit(
+            &mut context,
+            &pool_mint.pubkey(),
+            &user.pubkey(),
+            &deposit_authority,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.owner, user.pubkey());
+    assert_eq!(mining.amount, 50_000);
+
+    let reward_pool = RewardPool::unpack(
+        get_account(&mut context, &reward_pool_pubkey)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(reward_pool.total_amount, 50_000);
+
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    // the user claims rewards as usual
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim(
+            &mut context,
+            &user,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+
+    let user_reward = Account::unpack(
+        get_account(&mut context, &user_reward_account.pubkey())
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(user_reward.amount, 500);
+
+    test_reward_pool
+        .managed_withdraw(
+            &mut context,
+            &pool_mint.pubkey(),
+            &user.pubkey(),
+            &deposit_authority,
+            20_000,
+        )
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.amount, 30_000);
+
+    let reward_pool = RewardPool::unpack(
+        get_account(&mut context, &reward_pool_pubkey)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(reward_pool.total_amount, 30_000);
+
+    // the pool has balances now
+    assert!(test_reward_pool
+        .set_deposit_authority(&mut context, &pool_mint.pubkey(), None)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn fail_with_wrong_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let user = Keypair::new();
+
+    // pool is not managed
+    assert!(test_reward_pool
+        .managed_deposit(
+            &mut context,
+            &pool_mint.pubkey(),
+            &user.pubkey(),
+            &Keypair::new(),
+            1_000,
+        )
+        .await
+        .is_err());
+
+    let deposit_authority = Keypair::new();
+    test_reward_pool
+        .set_deposit_authority(
+            &mut context,
+            &pool_mint.pubkey(),
+            Some(deposit_authority.pubkey()),
+        )
+        .await
+        .unwrap();
+
+    assert!(test_reward_pool
+        .managed_deposit(
+            &mut context,
+            &pool_mint.pubkey(),
+            &user.pubkey(),
+            &Keypair::new(),
+            1_000,
+        )
+        .await
+        .is_err());
+
+    // token-backed deposits are rejected
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 1_000)
+        .await;
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            1_000,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &token_holder.token_account).await,
+        1_000
+    );
+}
@@ -0,0 +1,135 @@
+use crate::utils::*;
+use everlend_rewards::state::{
+    InitRewardPoolParams, Mining, PenaltyDestination, RewardPool, RewardVault, DEFAULT_MAX_VAULTS,
+    MAX_REWARDS,
+};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    // empty pool is allocated without vaults
+    assert_eq!(
+        get_account(&mut context, &reward_pool).await.data.len(),
+        RewardPool::size(0)
+    );
+
+    let max_vaults = DEFAULT_MAX_VAULTS + 3;
+    test_reward_pool
+        .update_pool(
+            &mut context,
+            &pool_mint.pubkey(),
+            0,
+            0,
+            0,
+            PenaltyDestination::Treasury,
+            max_vaults,
+        )
+        .await
+        .unwrap();
+
+    let (clock, _) = get_clock(&mut context).await;
+    for _ in 0..max_vaults {
+        let reward_mint = Keypair::new();
+        create_mint(&mut context, &reward_mint).await.unwrap();
+
+        test_reward_pool
+            .add_proportional_vault(
+                &mut context,
+                &pool_mint.pubkey(),
+                &reward_mint.pubkey(),
+                3600,
+                clock.unix_timestamp as u64,
+                1_000,
+            )
+            .await;
+    }
+
+    let reward_pool_account = get_account(&mut context, &reward_pool).await;
+    assert_eq!(
+        reward_pool_account.data.len(),
+        RewardPool::size(max_vaults as usize)
+    );
+    let reward_pool_state = RewardPool::unpack(reward_pool_account.data.borrow()).unwrap();
+    assert_eq!(reward_pool_state.vaults.len(), max_vaults as usize);
+
+    // limit can't go below the current vaults
+    assert!(test_reward_pool
+        .update_pool(
+            &mut context,
+            &pool_mint.pubkey(),
+            0,
+            0,
+            0,
+            PenaltyDestination::Treasury,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .is_err());
+
+    // mining grows with reward indexes
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 50_000)
+        .await;
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    let mining_account = get_account(&mut context, &mining_account).await;
+    assert_eq!(mining_account.data.len(), Mining::size(max_vaults as usize));
+    let mining = Mining::unpack(mining_account.data.borrow()).unwrap();
+    assert_eq!(mining.amount, 50_000);
+    assert_eq!(mining.indexes.len(), max_vaults as usize);
+}
+
+#[tokio::test]
+async fn vault_limit() {
+    let mut reward_pool = RewardPool::init(InitRewardPoolParams {
+        rewards_root: Pubkey::new_unique(),
+        bump: 0,
+        liquidity_mint: Pubkey::new_unique(),
+        lock_time_sec: 0,
+    });
+
+    for _ in 0..DEFAULT_MAX_VAULTS {
+        reward_pool
+            .add_vault(RewardVault {
+                reward_mint: Pubkey::new_unique(),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+
+    assert!(reward_pool
+        .add_vault(RewardVault {
+            reward_mint: Pubkey::new_unique(),
+            ..Default::default()
+        })
+        .is_err());
+
+    assert!(reward_pool
+        .update_params(0, 0, 0, PenaltyDestination::Treasury, MAX_REWARDS as u8 + 1)
+        .is_err());
+}
@@ -0,0 +1,462 @@
+use crate::utils::*;
+use borsh::BorshSerialize;
+use everlend_rewards::state::{
+    AccountType, Mining, MiningV1, PenaltyDestination, RewardIndexV1, RewardPool, RewardPoolV1,
+    RewardVaultV1, RewardsRoot, RewardsRootV1, DEFAULT_MAX_VAULTS,
+};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+async fn set_account_data(context: &mut ProgramTestContext, pubkey: &Pubkey, data: Vec<u8>) {
+    let mut account = get_account(context, pubkey).await;
+    account.data = data;
+    context.set_account(pubkey, &account.into());
+}
+
+/// Rewrite accounts with the first layout
+async fn rewrite_with_first_layout(
+    context: &mut ProgramTestContext,
+    test_reward_pool: &TestRewards,
+    reward_pool: &Pubkey,
+    mining_account: &Pubkey,
+    reward_mint: &Pubkey,
+    rewards: u64,
+) {
+    let rewards_root = test_reward_pool.rewards_root.pubkey();
+    let mut data = vec![0; RewardsRootV1::LEN];
+    RewardsRootV1 {
+        account_type: AccountType::RewardsRootV1,
+        authority: test_reward_pool.root_authority.pubkey(),
+    }
+    .serialize(&mut data.as_mut_slice())
+    .unwrap();
+    set_account_data(context, &rewards_root, data).await;
+
+    let pool = RewardPool::unpack(get_account(context, reward_pool).await.data.borrow()).unwrap();
+    let vault = &pool.vaults[0];
+    let mut data = vec![0; RewardPoolV1::LEN];
+    RewardPoolV1 {
+        account_type: AccountType::RewardPoolV1,
+        rewards_root,
+        bump: pool.bump,
+        liquidity_mint: pool.liquidity_mint,
+        total_amount: pool.total_amount,
+        lock_time_sec: pool.lock_time_sec,
+        vaults: vec![RewardVaultV1 {
+            bump: vault.bump,
+            reward_mint: vault.reward_mint,
+            ratio_base: vault.ratio_base,
+            ratio_quote: vault.ratio_quote,
+            reward_period_sec: vault.reward_period_sec,
+            distribution_starts_at: vault.distribution_starts_at,
+            reward_max_amount_per_period: vault.reward_max_amount_per_period,
+        }],
+    }
+    .serialize(&mut data.as_mut_slice())
+    .unwrap();
+    set_account_data(context, reward_pool, data).await;
+
+    let mining = Mining::unpack(get_account(context, mining_account).await.data.borrow()).unwrap();
+    let mut data = vec![0; MiningV1::LEN];
+    MiningV1 {
+        account_type: AccountType::MiningV1,
+        reward_pool: *reward_pool,
+        bump: mining.bump,
+        amount: mining.amount,
+        rewards_calculated_at: mining.rewards_calculated_at,
+        owner: mining.owner,
+        last_deposit_time: mining.last_deposit_time,
+        indexes: vec![RewardIndexV1 {
+            reward_mint: *reward_mint,
+            rewards,
+        }],
+    }
+    .serialize(&mut data.as_mut_slice())
+    .unwrap();
+    set_account_data(context, mining_account, data).await;
+}
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            3600,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            1_000_000,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1_000_000,
+        )
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 50_000)
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    rewrite_with_first_layout(
+        &mut context,
+        &test_reward_pool,
+        &reward_pool,
+        &mining_account,
+        &reward_mint.pubkey(),
+        100,
+    )
+    .await;
+
+    // deprecated accounts have to be migrated
+    let user_reward_account = Keypair::new();
+    assert!(test_reward_pool
+        .claim(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .is_err());
+
+    // root goes first
+    assert!(test_reward_pool
+        .migrate_pool(&mut context, &pool_mint.pubkey(), &[reward_mint.pubkey()])
+        .await
+        .is_err());
+
+    test_reward_pool.migrate_root(&mut context).await.unwrap();
+    let rewards_root = test_reward_pool.rewards_root.pubkey();
+
+    let rewards_root_account = get_account(&mut context, &rewards_root).await;
+    assert_eq!(rewards_root_account.data.len(), RewardsRoot::LEN);
+    let root = RewardsRoot::unpack(rewards_root_account.data.borrow()).unwrap();
+    assert_eq!(root.authority, test_reward_pool.root_authority.pubkey());
+
+    context.warp_to_slot(10).unwrap();
+
+    // liabilities of the previous layout are required
+    assert!(test_reward_pool
+        .migrate_pool(&mut context, &pool_mint.pubkey(), &[reward_mint.pubkey()])
+        .await
+        .is_err());
+
+    // liabilities have to match vaults of the pool
+    assert!(test_reward_pool
+        .migrate_pool_checked(
+            &mut context,
+            &pool_mint.pubkey(),
+            &[reward_mint.pubkey()],
+            vec![]
+        )
+        .await
+        .is_err());
+
+    // and can't exceed the vault balance
+    assert!(test_reward_pool
+        .migrate_pool_checked(
+            &mut context,
+            &pool_mint.pubkey(),
+            &[reward_mint.pubkey()],
+            vec![1_000_001],
+        )
+        .await
+        .is_err());
+
+    // unclaimed rewards of the deprecated mining stay owed
+    test_reward_pool
+        .migrate_pool_checked(
+            &mut context,
+            &pool_mint.pubkey(),
+            &[reward_mint.pubkey()],
+            vec![100],
+        )
+        .await
+        .unwrap();
+
+    let reward_pool_account = get_account(&mut context, &reward_pool).await;
+    assert_eq!(reward_pool_account.data.len(), RewardPool::size(1));
+    let pool = RewardPool::unpack(reward_pool_account.data.borrow()).unwrap();
+    assert_eq!(pool.account_type, AccountType::RewardPoolV2);
+    assert_eq!(pool.total_amount, 50_000);
+    assert_eq!(pool.total_weighted_amount, 50_000);
+    assert_eq!(pool.vaults[0].total_funded, 1_000_000);
+    assert_eq!(pool.vaults[0].total_accrued, 100);
+    assert_eq!(pool.vaults[0].available_budget(), 999_900);
+
+    context.warp_to_slot(12).unwrap();
+
+    // migrated pool can't be migrated again
+    assert!(test_reward_pool
+        .migrate_pool(&mut context, &pool_mint.pubkey(), &[reward_mint.pubkey()])
+        .await
+        .is_err());
+
+    clock.unix_timestamp += 3600;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(14).unwrap();
+
+    // mining is rewritten on the next update
+    test_reward_pool
+        .claim(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &user_reward_account.pubkey()).await,
+        600
+    );
+
+    let mining_account = get_account(&mut context, &mining_account).await;
+    assert_eq!(mining_account.data.len(), Mining::size(1));
+    let mining = Mining::unpack(mining_account.data.borrow()).unwrap();
+    assert_eq!(mining.account_type, AccountType::MiningV2);
+    assert_eq!(mining.amount, 50_000);
+    assert_eq!(mining.weighted_amount, 50_000);
+}
+
+#[tokio::test]
+async fn pending_rewards() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 3600)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            3600,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            1_000_000,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1_000_000,
+        )
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 50_000)
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    rewrite_with_first_layout(
+        &mut context,
+        &test_reward_pool,
+        &reward_pool,
+        &mining_account,
+        &reward_mint.pubkey(),
+        100,
+    )
+    .await;
+
+    // one whole period passes with the first layout
+    clock.unix_timestamp += 3600;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    test_reward_pool.migrate_root(&mut context).await.unwrap();
+
+    // flushed and pending rewards are reserved
+    test_reward_pool
+        .migrate_pool_checked(
+            &mut context,
+            &pool_mint.pubkey(),
+            &[reward_mint.pubkey()],
+            vec![600],
+        )
+        .await
+        .unwrap();
+
+    let pool =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(pool.migrated_at, clock.unix_timestamp as u64);
+    assert_eq!(pool.migrated_lock_time_sec, 3600);
+    assert_eq!(pool.vaults[0].total_accrued, 600);
+    assert_eq!(pool.vaults[0].legacy_reserve, 600);
+
+    // lock time raised after the migration doesn't apply to the previous deposit
+    test_reward_pool
+        .update_pool(
+            &mut context,
+            &pool_mint.pubkey(),
+            7200,
+            0,
+            0,
+            PenaltyDestination::Treasury,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .migrate_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.owner.pubkey(),
+        )
+        .await
+        .unwrap();
+
+    let pool =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(pool.vaults[0].total_accrued, 600);
+    assert_eq!(pool.vaults[0].legacy_reserve, 0);
+
+    let mining = Mining::unpack(
+        get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.account_type, AccountType::MiningV2);
+    assert_eq!(mining.indexes[0].rewards, 600);
+    assert_eq!(mining.locks[0].lock_time_sec, 3600);
+
+    context.warp_to_slot(12).unwrap();
+
+    // converted mining can't be converted again
+    assert!(test_reward_pool
+        .migrate_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.owner.pubkey(),
+        )
+        .await
+        .is_err());
+
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &user_reward_account.pubkey()).await,
+        600
+    );
+
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+            false,
+        )
+        .await
+        .unwrap();
+}
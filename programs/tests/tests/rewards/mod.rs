@@ -1,8 +1,25 @@
 pub mod add_vault;
+pub mod boost;
 pub mod claim;
 pub mod deposit_mining;
+pub mod early_exit;
 pub mod fill_vault;
 pub mod initialize_pool;
+pub mod legacy;
+pub mod managed;
+pub mod max_vaults;
+pub mod migrations;
+pub mod multisig;
+pub mod pause;
+pub mod receipt;
+pub mod remove_vault;
+pub mod roles;
+pub mod root_authority;
+pub mod timelock;
+pub mod transfer_mining;
+pub mod unbonding;
+pub mod update_pool;
+pub mod update_vault;
 pub mod withdraw_mining;
 
 use crate::utils::{
@@ -10,15 +27,22 @@ use crate::utils::{
     TokenHolder,
 };
 use anchor_lang::Key;
+use everlend_rewards::state::{
+    AdminAction, DistributionMode, PenaltyDestination, RewardsRoot, Role,
+};
 use everlend_rewards::{
-    find_mining_program_address, find_reward_pool_program_address,
-    find_reward_pool_spl_program_address, find_vault_program_address,
+    find_mining_program_address, find_pending_action_program_address,
+    find_receipt_mint_program_address, find_receipt_program_address,
+    find_reward_pool_program_address, find_reward_pool_spl_program_address,
+    find_vault_program_address,
 };
 use everlend_utils::find_program_address;
+use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
 use solana_program_test::ProgramTestContext;
 use solana_sdk::signature::{Keypair, Signer};
 use solana_sdk::transaction::Transaction;
+use std::borrow::Borrow;
 
 #[derive(Debug)]
 pub struct TestRewards {
@@ -31,7 +55,7 @@ impl TestRewards {
         let rewards_root = Keypair::new();
         let root_authority = Keypair::new();
 
-        transfer_sol(context, &root_authority.pubkey(), 10_000_000)
+        transfer_sol(context, &root_authority.pubkey(), 1_000_000_000)
             .await
             .unwrap();
 
@@ -105,6 +129,19 @@ impl TestRewards {
         user_token_account: &Pubkey,
         user: &Keypair,
         amount: u64,
+    ) -> BanksClientResult<Pubkey> {
+        self.deposit_mining_with_lock(context, liquidity_mint, user_token_account, user, amount, 0)
+            .await
+    }
+
+    pub async fn deposit_mining_with_lock(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user_token_account: &Pubkey,
+        user: &Keypair,
+        amount: u64,
+        lock_period_sec: u64,
     ) -> BanksClientResult<Pubkey> {
         let (reward_pool, reward_pool_spl) = self.get_pool_addresses(liquidity_mint);
 
@@ -112,8 +149,9 @@ impl TestRewards {
             find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
 
         let tx = Transaction::new_signed_with_payer(
-            &[everlend_rewards::instruction::deposit_mining(
+            &[everlend_rewards::instruction::deposit_mining_locked(
                 &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
                 &reward_pool,
                 &reward_pool_spl,
                 &liquidity_mint,
@@ -121,6 +159,7 @@ impl TestRewards {
                 user_token_account,
                 &user.pubkey(),
                 amount,
+                lock_period_sec,
             )],
             None,
             &[user],
@@ -138,6 +177,30 @@ impl TestRewards {
         liquidity_mint: &Pubkey,
         user_token_account: &Pubkey,
         user: &Keypair,
+        amount: u64,
+        forfeit: bool,
+    ) -> BanksClientResult<()> {
+        self.withdraw_mining_with_treasury(
+            context,
+            liquidity_mint,
+            user_token_account,
+            user,
+            amount,
+            forfeit,
+            None,
+        )
+        .await
+    }
+
+    pub async fn withdraw_mining_with_treasury(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user_token_account: &Pubkey,
+        user: &Keypair,
+        amount: u64,
+        forfeit: bool,
+        treasury_token_account: Option<&Pubkey>,
     ) -> BanksClientResult<()> {
         let (reward_pool, reward_pool_spl) = self.get_pool_addresses(liquidity_mint);
 
@@ -148,8 +211,9 @@ impl TestRewards {
             find_program_address(&everlend_rewards::id(), &reward_pool);
 
         let tx = Transaction::new_signed_with_payer(
-            &[everlend_rewards::instruction::withdraw_mining(
+            &[everlend_rewards::instruction::withdraw_mining_partial(
                 &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
                 &reward_pool,
                 &reward_pool_spl,
                 &reward_pool_authority,
@@ -157,6 +221,43 @@ impl TestRewards {
                 &mining_account,
                 user_token_account,
                 &user.pubkey(),
+                amount,
+                forfeit,
+                treasury_token_account,
+            )],
+            None,
+            &[user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn withdraw_mining_legacy(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user_token_account: &Pubkey,
+        user: &Keypair,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, reward_pool_spl) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+
+        let (reward_pool_authority, _) =
+            find_program_address(&everlend_rewards::id(), &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::withdraw_mining(
+                &everlend_rewards::id(),
+                &reward_pool,
+                &reward_pool_spl,
+                &reward_pool_authority,
+                liquidity_mint,
+                &mining_account,
+                user_token_account,
+                &user.pubkey(),
             )],
             None,
             &[user],
@@ -166,6 +267,95 @@ impl TestRewards {
         context.banks_client.process_transaction(tx).await
     }
 
+    pub async fn request_withdraw(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user: &Keypair,
+        amount: u64,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::request_withdraw(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &mining_account,
+                &user.pubkey(),
+                amount,
+            )],
+            Some(&user.pubkey()),
+            &[user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn finalize_withdraw(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user_token_account: &Pubkey,
+        user: &Keypair,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, reward_pool_spl) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+
+        let (reward_pool_authority, _) =
+            find_program_address(&everlend_rewards::id(), &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::finalize_withdraw(
+                &everlend_rewards::id(),
+                &reward_pool,
+                &reward_pool_spl,
+                &reward_pool_authority,
+                &mining_account,
+                user_token_account,
+                &user.pubkey(),
+            )],
+            Some(&user.pubkey()),
+            &[user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn cancel_withdraw(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user: &Keypair,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::cancel_withdraw(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &mining_account,
+                &user.pubkey(),
+            )],
+            Some(&user.pubkey()),
+            &[user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
     pub async fn add_vault(
         &self,
         context: &mut ProgramTestContext,
@@ -175,6 +365,59 @@ impl TestRewards {
         ratio_quote: u64,
         reward_period_sec: u32,
         distribution_starts_at: u64,
+    ) -> Pubkey {
+        self.add_vault_with_mode(
+            context,
+            liquidity_mint,
+            reward_mint,
+            ratio_base,
+            ratio_quote,
+            reward_period_sec,
+            distribution_starts_at,
+            None,
+            0,
+            DistributionMode::FixedRatio,
+        )
+        .await
+    }
+
+    pub async fn add_proportional_vault(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        reward_mint: &Pubkey,
+        reward_period_sec: u32,
+        distribution_starts_at: u64,
+        reward_amount_per_period: u64,
+    ) -> Pubkey {
+        self.add_vault_with_mode(
+            context,
+            liquidity_mint,
+            reward_mint,
+            0,
+            0,
+            reward_period_sec,
+            distribution_starts_at,
+            None,
+            reward_amount_per_period,
+            DistributionMode::Proportional,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_vault_with_mode(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        reward_mint: &Pubkey,
+        ratio_base: u64,
+        ratio_quote: u64,
+        reward_period_sec: u32,
+        distribution_starts_at: u64,
+        distribution_ends_at: Option<u64>,
+        reward_max_amount_per_period: u64,
+        distribution_mode: DistributionMode,
     ) -> Pubkey {
         let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
 
@@ -182,7 +425,7 @@ impl TestRewards {
             find_vault_program_address(&everlend_rewards::id(), &reward_pool, reward_mint);
 
         let tx = Transaction::new_signed_with_payer(
-            &[everlend_rewards::instruction::add_vault(
+            &[everlend_rewards::instruction::add_vault_extended(
                 &everlend_rewards::id(),
                 &self.rewards_root.pubkey(),
                 &reward_pool,
@@ -193,7 +436,9 @@ impl TestRewards {
                 ratio_quote,
                 reward_period_sec,
                 distribution_starts_at,
-                0,
+                distribution_ends_at,
+                reward_max_amount_per_period,
+                distribution_mode,
             )],
             Some(&self.root_authority.pubkey()),
             &[&self.root_authority],
@@ -205,64 +450,995 @@ impl TestRewards {
         vault_pubkey
     }
 
-    pub async fn fill_vault(
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_vault(
         &self,
         context: &mut ProgramTestContext,
-        from: &TokenHolder,
         liquidity_mint: &Pubkey,
         reward_mint: &Pubkey,
-        amount: u64,
+        ratio_base: u64,
+        ratio_quote: u64,
+        reward_period_sec: u32,
+        distribution_starts_at: u64,
+        distribution_ends_at: Option<u64>,
+        reward_max_amount_per_period: u64,
     ) -> BanksClientResult<()> {
         let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
-        let (vault_pubkey, _) =
-            find_vault_program_address(&everlend_rewards::id(), &reward_pool, reward_mint);
 
         let tx = Transaction::new_signed_with_payer(
-            &[everlend_rewards::instruction::fill_vault(
+            &[everlend_rewards::instruction::update_vault(
                 &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
                 &reward_pool,
                 reward_mint,
-                &vault_pubkey,
-                &from.token_account.key(),
-                &from.owner.pubkey(),
-                amount,
+                &self.root_authority.pubkey(),
+                ratio_base,
+                ratio_quote,
+                reward_period_sec,
+                distribution_starts_at,
+                distribution_ends_at,
+                reward_max_amount_per_period,
             )],
-            None,
-            &[&from.owner],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
             context.last_blockhash,
         );
 
         context.banks_client.process_transaction(tx).await
     }
 
-    pub async fn claim(
+    pub async fn sunset_vault(
         &self,
         context: &mut ProgramTestContext,
-        user: &Keypair,
         liquidity_mint: &Pubkey,
         reward_mint: &Pubkey,
-        user_reward_token_account: &Keypair,
+        claim_period_sec: u64,
     ) -> BanksClientResult<()> {
         let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
 
-        let (mining_account, _) =
-            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::sunset_vault(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                reward_mint,
+                &self.root_authority.pubkey(),
+                claim_period_sec,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
 
-        let (vault_pubkey, _) =
-            find_vault_program_address(&everlend_rewards::id(), &reward_pool, reward_mint);
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn remove_vault(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        reward_mint: &Pubkey,
+        destination: &Pubkey,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
 
         let tx = Transaction::new_signed_with_payer(
-            &[everlend_rewards::instruction::claim(
+            &[everlend_rewards::instruction::remove_vault(
                 &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
                 &reward_pool,
                 reward_mint,
-                &vault_pubkey,
-                &mining_account,
-                &user.pubkey(),
-                &user_reward_token_account.pubkey(),
+                destination,
+                &self.root_authority.pubkey(),
             )],
-            None,
-            &[user, user_reward_token_account],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn propose_root_authority(
+        &self,
+        context: &mut ProgramTestContext,
+        new_authority: &Pubkey,
+    ) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::propose_root_authority(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &self.root_authority.pubkey(),
+                new_authority,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn accept_root_authority(
+        &self,
+        context: &mut ProgramTestContext,
+        new_authority: &Keypair,
+    ) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::accept_root_authority(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &new_authority.pubkey(),
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority, new_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn update_pool(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        lock_time_sec: u64,
+        unbonding_period_sec: u64,
+        early_exit_penalty_bps: u16,
+        penalty_destination: PenaltyDestination,
+        max_vaults: u8,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::update_pool(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &self.root_authority.pubkey(),
+                lock_time_sec,
+                unbonding_period_sec,
+                early_exit_penalty_bps,
+                penalty_destination,
+                max_vaults,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn set_treasury(
+        &self,
+        context: &mut ProgramTestContext,
+        treasury: &Pubkey,
+    ) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::set_treasury(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &self.root_authority.pubkey(),
+                treasury,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn set_role(
+        &self,
+        context: &mut ProgramTestContext,
+        role: Role,
+        account: &Pubkey,
+    ) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::set_role(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &self.root_authority.pubkey(),
+                account,
+                role,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn set_deposit_authority(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        deposit_authority: Option<Pubkey>,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::set_deposit_authority(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &self.root_authority.pubkey(),
+                deposit_authority,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn managed_deposit(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user: &Pubkey,
+        deposit_authority: &Keypair,
+        amount: u64,
+    ) -> BanksClientResult<Pubkey> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+        let (mining, _) = find_mining_program_address(&everlend_rewards::id(), user, &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::managed_deposit(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &mining,
+                user,
+                &deposit_authority.pubkey(),
+                &context.payer.pubkey(),
+                amount,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, deposit_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await?;
+
+        Ok(mining)
+    }
+
+    pub async fn managed_withdraw(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user: &Pubkey,
+        deposit_authority: &Keypair,
+        amount: u64,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+        let (mining, _) = find_mining_program_address(&everlend_rewards::id(), user, &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::managed_withdraw(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &mining,
+                user,
+                &deposit_authority.pubkey(),
+                &context.payer.pubkey(),
+                amount,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, deposit_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn set_root_paused(
+        &self,
+        context: &mut ProgramTestContext,
+        pauser: &Keypair,
+        paused: bool,
+    ) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::set_root_paused(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &pauser.pubkey(),
+                paused,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, pauser],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn set_pool_paused(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        pauser: &Keypair,
+        paused: bool,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::set_pool_paused(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &pauser.pubkey(),
+                paused,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, pauser],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn sync_root_paused(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::sync_root_paused(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn emergency_withdraw(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user_token_account: &Pubkey,
+        user: &Keypair,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, reward_pool_spl) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+
+        let (reward_pool_authority, _) =
+            find_program_address(&everlend_rewards::id(), &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::emergency_withdraw(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &reward_pool_spl,
+                &reward_pool_authority,
+                &mining_account,
+                user_token_account,
+                &user.pubkey(),
+            )],
+            Some(&user.pubkey()),
+            &[user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn propose_action(
+        &self,
+        context: &mut ProgramTestContext,
+        action: AdminAction,
+    ) -> BanksClientResult<Pubkey> {
+        let rewards_root_account = get_account(context, &self.rewards_root.pubkey()).await;
+        let rewards_root = RewardsRoot::unpack(rewards_root_account.data.borrow()).unwrap();
+
+        let (pending_action, _) = find_pending_action_program_address(
+            &everlend_rewards::id(),
+            &self.rewards_root.pubkey(),
+            rewards_root.next_action_id,
+        );
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::propose_action(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &pending_action,
+                &self.root_authority.pubkey(),
+                action,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await?;
+
+        Ok(pending_action)
+    }
+
+    pub async fn execute_action(
+        &self,
+        context: &mut ProgramTestContext,
+        pending_action: &Pubkey,
+        action: &AdminAction,
+    ) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::execute_action(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                pending_action,
+                &self.root_authority.pubkey(),
+                action,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn cancel_action(
+        &self,
+        context: &mut ProgramTestContext,
+        pending_action: &Pubkey,
+        authority: &Keypair,
+    ) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::cancel_action(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                pending_action,
+                &self.root_authority.pubkey(),
+                &authority.pubkey(),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn initialize_multisig(
+        &self,
+        context: &mut ProgramTestContext,
+        multisig: &Keypair,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::initialize_multisig(
+                &everlend_rewards::id(),
+                &multisig.pubkey(),
+                &context.payer.pubkey(),
+                signers,
+                threshold,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, multisig],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn add_funder(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        funder: &Pubkey,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::add_funder(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &self.root_authority.pubkey(),
+                funder,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn remove_funder(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        funder: &Pubkey,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::remove_funder(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &self.root_authority.pubkey(),
+                funder,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn fill_vault(
+        &self,
+        context: &mut ProgramTestContext,
+        from: &TokenHolder,
+        liquidity_mint: &Pubkey,
+        reward_mint: &Pubkey,
+        amount: u64,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+        let (vault_pubkey, _) =
+            find_vault_program_address(&everlend_rewards::id(), &reward_pool, reward_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::fill_vault_checked(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                reward_mint,
+                &vault_pubkey,
+                &from.token_account.key(),
+                &from.owner.pubkey(),
+                amount,
+            )],
+            None,
+            &[&from.owner],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn transfer_mining(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user: &Keypair,
+        destination_user: &Keypair,
+        amount: u64,
+    ) -> BanksClientResult<Pubkey> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+        let (destination_mining, _) = find_mining_program_address(
+            &everlend_rewards::id(),
+            &destination_user.pubkey(),
+            &reward_pool,
+        );
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::transfer_mining(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &mining,
+                &destination_mining,
+                &user.pubkey(),
+                &destination_user.pubkey(),
+                amount,
+            )],
+            Some(&user.pubkey()),
+            &[user, destination_user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await?;
+
+        Ok(destination_mining)
+    }
+
+    pub async fn initialize_receipt_mint(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+    ) -> BanksClientResult<Pubkey> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+        let (receipt_mint, _) =
+            find_receipt_mint_program_address(&everlend_rewards::id(), &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::initialize_receipt_mint(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &receipt_mint,
+                liquidity_mint,
+                &self.root_authority.pubkey(),
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await?;
+
+        Ok(receipt_mint)
+    }
+
+    pub fn get_receipt_addresses(
+        &self,
+        liquidity_mint: &Pubkey,
+        user: &Pubkey,
+    ) -> (Pubkey, Pubkey) {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+        let (mining, _) = find_mining_program_address(&everlend_rewards::id(), user, &reward_pool);
+        let (receipt_mint, _) =
+            find_receipt_mint_program_address(&everlend_rewards::id(), &reward_pool);
+        let (receipt_account, _) = find_receipt_program_address(&everlend_rewards::id(), &mining);
+
+        (receipt_mint, receipt_account)
+    }
+
+    pub async fn deposit_mining_with_receipt(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user_token_account: &Pubkey,
+        user: &Keypair,
+        amount: u64,
+    ) -> BanksClientResult<Pubkey> {
+        let (reward_pool, reward_pool_spl) = self.get_pool_addresses(liquidity_mint);
+        let (mining, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+        let (receipt_mint, receipt_account) =
+            self.get_receipt_addresses(liquidity_mint, &user.pubkey());
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::with_receipt(
+                everlend_rewards::instruction::deposit_mining_locked(
+                    &everlend_rewards::id(),
+                    &self.rewards_root.pubkey(),
+                    &reward_pool,
+                    &reward_pool_spl,
+                    liquidity_mint,
+                    &mining,
+                    user_token_account,
+                    &user.pubkey(),
+                    amount,
+                    0,
+                ),
+                &receipt_mint,
+                &receipt_account,
+            )],
+            Some(&user.pubkey()),
+            &[user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await?;
+
+        Ok(receipt_account)
+    }
+
+    pub async fn withdraw_mining_with_receipt(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user_token_account: &Pubkey,
+        user: &Keypair,
+        amount: u64,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, reward_pool_spl) = self.get_pool_addresses(liquidity_mint);
+        let (mining, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+        let (reward_pool_authority, _) =
+            find_program_address(&everlend_rewards::id(), &reward_pool);
+        let (receipt_mint, receipt_account) =
+            self.get_receipt_addresses(liquidity_mint, &user.pubkey());
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::with_receipt(
+                everlend_rewards::instruction::withdraw_mining_partial(
+                    &everlend_rewards::id(),
+                    &self.rewards_root.pubkey(),
+                    &reward_pool,
+                    &reward_pool_spl,
+                    &reward_pool_authority,
+                    liquidity_mint,
+                    &mining,
+                    user_token_account,
+                    &user.pubkey(),
+                    amount,
+                    false,
+                    None,
+                ),
+                &receipt_mint,
+                &receipt_account,
+            )],
+            Some(&user.pubkey()),
+            &[user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn settle_receipt(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user: &Pubkey,
+    ) -> BanksClientResult<Pubkey> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+        let (mining, _) = find_mining_program_address(&everlend_rewards::id(), user, &reward_pool);
+        let (receipt_mint, receipt_account) = self.get_receipt_addresses(liquidity_mint, user);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::settle_receipt(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &mining,
+                user,
+                &context.payer.pubkey(),
+                &receipt_mint,
+                &receipt_account,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await?;
+
+        Ok(mining)
+    }
+
+    pub async fn migrate_root(&self, context: &mut ProgramTestContext) -> BanksClientResult<()> {
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::migrate_root(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &self.root_authority.pubkey(),
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn migrate_pool(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        reward_mints: &[Pubkey],
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::migrate_pool(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &self.root_authority.pubkey(),
+                liquidity_mint,
+                reward_mints,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn migrate_pool_checked(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        reward_mints: &[Pubkey],
+        liabilities: Vec<u64>,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::migrate_pool_checked(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &self.root_authority.pubkey(),
+                liquidity_mint,
+                reward_mints,
+                liabilities,
+            )],
+            Some(&self.root_authority.pubkey()),
+            &[&self.root_authority],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn migrate_mining(
+        &self,
+        context: &mut ProgramTestContext,
+        liquidity_mint: &Pubkey,
+        user: &Pubkey,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), user, &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::migrate_mining(
+                &everlend_rewards::id(),
+                &reward_pool,
+                &mining_account,
+                &context.payer.pubkey(),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn claim(
+        &self,
+        context: &mut ProgramTestContext,
+        user: &Keypair,
+        liquidity_mint: &Pubkey,
+        reward_mint: &Pubkey,
+        user_reward_token_account: &Keypair,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+
+        let (vault_pubkey, _) =
+            find_vault_program_address(&everlend_rewards::id(), &reward_pool, reward_mint);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::claim_checked(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                reward_mint,
+                &vault_pubkey,
+                &mining_account,
+                &user.pubkey(),
+                &user_reward_token_account.pubkey(),
+            )],
+            Some(&user.pubkey()),
+            &[user, user_reward_token_account],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn claim_all(
+        &self,
+        context: &mut ProgramTestContext,
+        user: &Keypair,
+        liquidity_mint: &Pubkey,
+        rewards: &[(Pubkey, Pubkey)],
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::claim_all(
+                &everlend_rewards::id(),
+                &self.rewards_root.pubkey(),
+                &reward_pool,
+                &mining_account,
+                &user.pubkey(),
+                rewards,
+            )],
+            Some(&user.pubkey()),
+            &[user],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn claim_with_receipt(
+        &self,
+        context: &mut ProgramTestContext,
+        user: &Keypair,
+        liquidity_mint: &Pubkey,
+        reward_mint: &Pubkey,
+        user_reward_token_account: &Keypair,
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+
+        let (vault_pubkey, _) =
+            find_vault_program_address(&everlend_rewards::id(), &reward_pool, reward_mint);
+        let (receipt_mint, receipt_account) =
+            self.get_receipt_addresses(liquidity_mint, &user.pubkey());
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::with_receipt(
+                everlend_rewards::instruction::claim_checked(
+                    &everlend_rewards::id(),
+                    &self.rewards_root.pubkey(),
+                    &reward_pool,
+                    reward_mint,
+                    &vault_pubkey,
+                    &mining_account,
+                    &user.pubkey(),
+                    &user_reward_token_account.pubkey(),
+                ),
+                &receipt_mint,
+                &receipt_account,
+            )],
+            Some(&user.pubkey()),
+            &[user, user_reward_token_account],
+            context.last_blockhash,
+        );
+
+        context.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn claim_all_with_receipt(
+        &self,
+        context: &mut ProgramTestContext,
+        user: &Keypair,
+        liquidity_mint: &Pubkey,
+        rewards: &[(Pubkey, Pubkey)],
+    ) -> BanksClientResult<()> {
+        let (reward_pool, _) = self.get_pool_addresses(liquidity_mint);
+
+        let (mining_account, _) =
+            find_mining_program_address(&everlend_rewards::id(), &user.pubkey(), &reward_pool);
+        let (receipt_mint, receipt_account) =
+            self.get_receipt_addresses(liquidity_mint, &user.pubkey());
+
+        let tx = Transaction::new_signed_with_payer(
+            &[everlend_rewards::instruction::with_receipt(
+                everlend_rewards::instruction::claim_all(
+                    &everlend_rewards::id(),
+                    &self.rewards_root.pubkey(),
+                    &reward_pool,
+                    &mining_account,
+                    &user.pubkey(),
+                    rewards,
+                ),
+                &receipt_mint,
+                &receipt_account,
+            )],
+            Some(&user.pubkey()),
+            &[user],
             context.last_blockhash,
         );
 
@@ -0,0 +1,210 @@
+use crate::utils::*;
+use everlend_rewards::instruction;
+use everlend_rewards::state::RewardsRoot;
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &Keypair::new(), 0)
+        .await
+        .unwrap();
+
+    let signers = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+    let multisig = Keypair::new();
+    test_reward_pool
+        .initialize_multisig(
+            &mut context,
+            &multisig,
+            signers.iter().map(|s| s.pubkey()).collect(),
+            2,
+        )
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .propose_root_authority(&mut context, &multisig.pubkey())
+        .await
+        .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::with_multisig(
+            instruction::accept_root_authority(
+                &everlend_rewards::id(),
+                &test_reward_pool.rewards_root.pubkey(),
+                &signers[0].pubkey(),
+            ),
+            &multisig.pubkey(),
+            &[&signers[2].pubkey()],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &signers[0], &signers[2]],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let rewards_root_account =
+        get_account(&mut context, &test_reward_pool.rewards_root.pubkey()).await;
+    let rewards_root = RewardsRoot::unpack(rewards_root_account.data.borrow()).unwrap();
+    assert_eq!(rewards_root.authority, multisig.pubkey());
+
+    let treasury = Keypair::new().pubkey();
+
+    // a single signature is below the threshold
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::with_multisig(
+            instruction::set_treasury(
+                &everlend_rewards::id(),
+                &test_reward_pool.rewards_root.pubkey(),
+                &signers[1].pubkey(),
+                &treasury,
+            ),
+            &multisig.pubkey(),
+            &[],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &signers[1]],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err());
+
+    // the same signer twice doesn't count
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::with_multisig(
+            instruction::set_treasury(
+                &everlend_rewards::id(),
+                &test_reward_pool.rewards_root.pubkey(),
+                &signers[1].pubkey(),
+                &treasury,
+            ),
+            &multisig.pubkey(),
+            &[&signers[1].pubkey()],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &signers[1]],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err());
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::with_multisig(
+            instruction::set_treasury(
+                &everlend_rewards::id(),
+                &test_reward_pool.rewards_root.pubkey(),
+                &signers[1].pubkey(),
+                &treasury,
+            ),
+            &multisig.pubkey(),
+            &[&signers[0].pubkey()],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &signers[1], &signers[0]],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let rewards_root_account =
+        get_account(&mut context, &test_reward_pool.rewards_root.pubkey()).await;
+    let rewards_root = RewardsRoot::unpack(rewards_root_account.data.borrow()).unwrap();
+    assert_eq!(rewards_root.treasury, treasury);
+}
+
+#[tokio::test]
+async fn invalid_threshold() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let signers = vec![Keypair::new().pubkey(), Keypair::new().pubkey()];
+    assert!(test_reward_pool
+        .initialize_multisig(&mut context, &Keypair::new(), signers, 3)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn fail_with_multisig_keypair() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &Keypair::new(), 0)
+        .await
+        .unwrap();
+
+    let signers = vec![Keypair::new(), Keypair::new()];
+    let multisig = Keypair::new();
+    test_reward_pool
+        .initialize_multisig(
+            &mut context,
+            &multisig,
+            signers.iter().map(|s| s.pubkey()).collect(),
+            2,
+        )
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .propose_root_authority(&mut context, &multisig.pubkey())
+        .await
+        .unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::with_multisig(
+            instruction::accept_root_authority(
+                &everlend_rewards::id(),
+                &test_reward_pool.rewards_root.pubkey(),
+                &signers[0].pubkey(),
+            ),
+            &multisig.pubkey(),
+            &[&signers[1].pubkey()],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &signers[0], &signers[1]],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    // keypair of the multisig account doesn't bypass the threshold
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::set_treasury(
+            &everlend_rewards::id(),
+            &test_reward_pool.rewards_root.pubkey(),
+            &multisig.pubkey(),
+            &Keypair::new().pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &multisig],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err());
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::with_multisig(
+            instruction::set_treasury(
+                &everlend_rewards::id(),
+                &test_reward_pool.rewards_root.pubkey(),
+                &multisig.pubkey(),
+                &Keypair::new().pubkey(),
+            ),
+            &multisig.pubkey(),
+            &[],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &multisig],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err());
+}
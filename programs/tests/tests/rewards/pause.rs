@@ -0,0 +1,303 @@
+use crate::utils::*;
+use everlend_rewards::state::{RewardPool, Role};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let liquidity_mint = Keypair::new();
+    let initial_balance = 10_000;
+    let deposit_amount = 1000;
+
+    let (reward_pool, reward_pool_spl) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 3600)
+        .await
+        .unwrap();
+
+    let pauser = Keypair::new();
+    test_reward_pool
+        .set_role(&mut context, Role::Pauser, &pauser.pubkey())
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            initial_balance,
+        )
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    // emergency withdraw is available only while paused
+    test_reward_pool
+        .emergency_withdraw(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+        )
+        .await
+        .unwrap_err();
+
+    test_reward_pool
+        .set_pool_paused(&mut context, &liquidity_mint.pubkey(), &pauser, true)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .request_withdraw(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap_err();
+
+    context.warp_to_slot(3).unwrap();
+
+    // principal is returned regardless of the lock
+    test_reward_pool
+        .emergency_withdraw(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+        )
+        .await
+        .unwrap();
+
+    let token_balance = get_token_balance(&mut context, &token_holder.token_account).await;
+    assert_eq!(token_balance, initial_balance);
+
+    let pool_token_balance = get_token_balance(&mut context, &reward_pool_spl).await;
+    assert_eq!(pool_token_balance, 0);
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool_account.total_amount, 0);
+    assert_eq!(reward_pool_account.total_weighted_amount, 0);
+
+    assert!(context
+        .banks_client
+        .get_account(mining_account)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn root_paused() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let liquidity_mint = Keypair::new();
+    let deposit_amount = 1000;
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            deposit_amount,
+        )
+        .await;
+
+    // only authority or pauser can pause
+    test_reward_pool
+        .set_root_paused(&mut context, &Keypair::new(), true)
+        .await
+        .unwrap_err();
+
+    let authority = Keypair::from_bytes(&test_reward_pool.root_authority.to_bytes()).unwrap();
+    test_reward_pool
+        .set_root_paused(&mut context, &authority, true)
+        .await
+        .unwrap();
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(mining_account)
+        .await
+        .unwrap()
+        .is_none());
+
+    test_reward_pool
+        .set_root_paused(&mut context, &authority, false)
+        .await
+        .unwrap();
+
+    context.warp_to_slot(3).unwrap();
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    let token_balance = get_token_balance(&mut context, &token_holder.token_account).await;
+    assert_eq!(token_balance, 0);
+}
+
+#[tokio::test]
+async fn forfeited_rewards() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let liquidity_mint = Keypair::new();
+    let deposit_amount = 1000;
+
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    test_reward_pool
+        .add_proportional_vault(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_period,
+            clock.unix_timestamp as u64,
+            1_000,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(&mut context, &reward_mint.pubkey(), 10_000_000_000, 10_000)
+        .await;
+    test_reward_pool
+        .add_funder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &rewarder.owner.pubkey(),
+        )
+        .await
+        .unwrap();
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &liquidity_mint.pubkey(),
+            &reward_mint.pubkey(),
+            10_000,
+        )
+        .await
+        .unwrap();
+
+    let mut users = vec![];
+    for _ in 0..2 {
+        users.push(
+            test_reward_pool
+                .create_token_holder(
+                    &mut context,
+                    &liquidity_mint.pubkey(),
+                    10_000_000_000,
+                    deposit_amount,
+                )
+                .await,
+        );
+    }
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &users[0].token_account,
+            &users[0].owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(3).unwrap();
+
+    // the whole period is accrued to the first user by the vault index
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &users[1].token_account,
+            &users[1].owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool_account.vaults[0].total_accrued, 1_000);
+
+    let authority = Keypair::from_bytes(&test_reward_pool.root_authority.to_bytes()).unwrap();
+    test_reward_pool
+        .set_pool_paused(&mut context, &liquidity_mint.pubkey(), &authority, true)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .emergency_withdraw(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &users[0].token_account,
+            &users[0].owner,
+        )
+        .await
+        .unwrap();
+
+    // unclaimed rewards of the closed mining are returned to the budget
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool_account.vaults[0].total_accrued, 0);
+}
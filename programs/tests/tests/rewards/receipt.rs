@@ -0,0 +1,499 @@
+use crate::utils::*;
+use everlend_rewards::state::{Mining, RewardPool};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use spl_token::state::Mint;
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let receipt_mint = test_reward_pool
+        .initialize_receipt_mint(&mut context, &pool_mint.pubkey())
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            3600,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            1_000_000,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1_000_000,
+        )
+        .await
+        .unwrap();
+
+    // receipts are minted 1:1
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 50_000)
+        .await;
+
+    let receipt_account = test_reward_pool
+        .deposit_mining_with_receipt(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &receipt_account).await,
+        50_000
+    );
+
+    clock.unix_timestamp += 3600;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    // receipts change hands and both sides are settled
+    let receiver = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 0)
+        .await;
+    test_reward_pool
+        .settle_receipt(&mut context, &pool_mint.pubkey(), &receiver.owner.pubkey())
+        .await
+        .unwrap();
+    let (_, receiver_receipt_account) =
+        test_reward_pool.get_receipt_addresses(&pool_mint.pubkey(), &receiver.owner.pubkey());
+
+    token_transfer(
+        &mut context,
+        &receipt_account,
+        &receiver_receipt_account,
+        &token_holder.owner,
+        20_000,
+    )
+    .await
+    .unwrap();
+    context.warp_to_slot(12).unwrap();
+
+    let mining_account = test_reward_pool
+        .settle_receipt(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.owner.pubkey(),
+        )
+        .await
+        .unwrap();
+    let receiver_mining_account = test_reward_pool
+        .settle_receipt(&mut context, &pool_mint.pubkey(), &receiver.owner.pubkey())
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.amount, 30_000);
+    // rewards since the last settlement accrue only to the amount held throughout
+    assert_eq!(mining.indexes[0].rewards, 300);
+
+    let receiver_mining = Mining::unpack(
+        get_account(&mut context, &receiver_mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(receiver_mining.amount, 20_000);
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool_account.total_amount, 50_000);
+
+    // withdraw burns receipts
+    test_reward_pool
+        .withdraw_mining_with_receipt(
+            &mut context,
+            &pool_mint.pubkey(),
+            &receiver.token_account,
+            &receiver.owner,
+            20_000,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &receiver.token_account).await,
+        20_000
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &receiver_receipt_account).await,
+        0
+    );
+
+    let receipt_mint_account = get_account(&mut context, &receipt_mint).await;
+    let receipt_mint = Mint::unpack(receipt_mint_account.data.borrow()).unwrap();
+    assert_eq!(receipt_mint.supply, 30_000);
+
+    // transferred receipts can't be withdrawn by the previous holder
+    assert!(test_reward_pool
+        .withdraw_mining_with_receipt(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn fail_with_locked_pool() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 3600)
+        .await
+        .unwrap();
+
+    // lock could be bypassed by a receipt transfer
+    assert!(test_reward_pool
+        .initialize_receipt_mint(&mut context, &pool_mint.pubkey())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn claim_after_transfer() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .initialize_receipt_mint(&mut context, &pool_mint.pubkey())
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    test_reward_pool
+        .add_proportional_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            3600,
+            clock.unix_timestamp as u64,
+            1000,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            1_000_000,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1_000_000,
+        )
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 1000)
+        .await;
+
+    let receipt_account = test_reward_pool
+        .deposit_mining_with_receipt(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            1000,
+        )
+        .await
+        .unwrap();
+
+    let receiver = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 0)
+        .await;
+    test_reward_pool
+        .settle_receipt(&mut context, &pool_mint.pubkey(), &receiver.owner.pubkey())
+        .await
+        .unwrap();
+    let (_, receiver_receipt_account) =
+        test_reward_pool.get_receipt_addresses(&pool_mint.pubkey(), &receiver.owner.pubkey());
+
+    token_transfer(
+        &mut context,
+        &receipt_account,
+        &receiver_receipt_account,
+        &token_holder.owner,
+        500,
+    )
+    .await
+    .unwrap();
+    context.warp_to_slot(5).unwrap();
+
+    // claims settle both sides with their receipt balances
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim_with_receipt(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+
+    let receiver_reward_account = Keypair::new();
+    create_token_account(
+        &mut context,
+        &receiver_reward_account,
+        &reward_mint.pubkey(),
+        &receiver.owner.pubkey(),
+        0,
+    )
+    .await
+    .unwrap();
+    let rewards = [(reward_mint.pubkey(), receiver_reward_account.pubkey())];
+    test_reward_pool
+        .claim_all_with_receipt(&mut context, &receiver.owner, &pool_mint.pubkey(), &rewards)
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += 3600;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    test_reward_pool
+        .claim_with_receipt(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+    test_reward_pool
+        .claim_all_with_receipt(&mut context, &receiver.owner, &pool_mint.pubkey(), &rewards)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &user_reward_account.pubkey()).await,
+        500
+    );
+    assert_eq!(
+        get_token_balance(&mut context, &receiver_reward_account.pubkey()).await,
+        500
+    );
+}
+
+#[tokio::test]
+async fn claim_after_unsettled_transfer() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .initialize_receipt_mint(&mut context, &pool_mint.pubkey())
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    // 500 per period for the whole deposit
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            3600,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            1_000_000,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1_000_000,
+        )
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 50_000)
+        .await;
+
+    let receipt_account = test_reward_pool
+        .deposit_mining_with_receipt(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    let receiver = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 0)
+        .await;
+    test_reward_pool
+        .settle_receipt(&mut context, &pool_mint.pubkey(), &receiver.owner.pubkey())
+        .await
+        .unwrap();
+    let (_, receiver_receipt_account) =
+        test_reward_pool.get_receipt_addresses(&pool_mint.pubkey(), &receiver.owner.pubkey());
+
+    token_transfer(
+        &mut context,
+        &receipt_account,
+        &receiver_receipt_account,
+        &token_holder.owner,
+        20_000,
+    )
+    .await
+    .unwrap();
+    context.warp_to_slot(5).unwrap();
+
+    // only the receiver is settled, the sender still has the previous amount
+    test_reward_pool
+        .settle_receipt(&mut context, &pool_mint.pubkey(), &receiver.owner.pubkey())
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += 3600;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim_with_receipt(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+    let receiver_reward_account = Keypair::new();
+    test_reward_pool
+        .claim_with_receipt(
+            &mut context,
+            &receiver.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &receiver_reward_account,
+        )
+        .await
+        .unwrap();
+
+    // transferred stake isn't counted on both sides
+    let user_rewards = get_token_balance(&mut context, &user_reward_account.pubkey()).await;
+    let receiver_rewards = get_token_balance(&mut context, &receiver_reward_account.pubkey()).await;
+    assert!(user_rewards + receiver_rewards <= 500);
+    assert_eq!(user_rewards, 300);
+    assert_eq!(receiver_rewards, 200);
+}
@@ -0,0 +1,355 @@
+use crate::utils::*;
+use everlend_rewards::state::{Mining, RewardPool, MIN_CLAIM_PERIOD_SEC};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+
+    let (reward_pool_pubkey, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    let vault = test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            reward_period,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let reward_amount = 1_000_000;
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            reward_amount,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await
+        .unwrap();
+
+    let deposit_amount = 50_000;
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &pool_mint.pubkey(),
+            10_000_000_000,
+            deposit_amount,
+        )
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.clone().into());
+    context.warp_to_slot(10).unwrap();
+
+    test_reward_pool
+        .sunset_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            MIN_CLAIM_PERIOD_SEC,
+        )
+        .await
+        .unwrap();
+
+    // claim period is still active
+    test_reward_pool
+        .remove_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &rewarder.token_account,
+        )
+        .await
+        .unwrap_err();
+
+    clock.unix_timestamp += MIN_CLAIM_PERIOD_SEC as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(20).unwrap();
+
+    // rewards are accrued only until the end of distribution
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+
+    let user_reward = get_token_balance(&mut context, &user_reward_account.pubkey()).await;
+    assert_eq!(user_reward, 500);
+
+    let pool_lamports = get_account(&mut context, &reward_pool_pubkey)
+        .await
+        .lamports;
+
+    test_reward_pool
+        .remove_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &rewarder.token_account,
+        )
+        .await
+        .unwrap();
+
+    let rewarder_balance = get_token_balance(&mut context, &rewarder.token_account).await;
+    assert_eq!(rewarder_balance, reward_amount - 500);
+
+    assert!(context
+        .banks_client
+        .get_account(vault)
+        .await
+        .unwrap()
+        .is_none());
+
+    let reward_pool = RewardPool::unpack(
+        &get_account(&mut context, &reward_pool_pubkey)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert!(reward_pool.vaults.is_empty());
+
+    // pool account shrinks with removed vault, its rent stays for new ones
+    let reward_pool_account = get_account(&mut context, &reward_pool_pubkey).await;
+    assert_eq!(reward_pool_account.data.len(), RewardPool::size(0));
+    assert_eq!(reward_pool_account.lamports, pool_lamports);
+
+    // stale reward index is removed on refresh
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount / 2,
+            false,
+        )
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        &get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert!(mining.indexes.is_empty());
+
+    let mining_account = get_account(&mut context, &mining_account).await;
+    assert_eq!(mining_account.data.len(), Mining::size(0));
+}
+
+#[tokio::test]
+async fn readded_vault() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    test_reward_pool
+        .add_proportional_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_period,
+            clock.unix_timestamp as u64,
+            1000,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            1_000_000,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100_000,
+        )
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 50_000)
+        .await;
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.clone().into());
+    context.warp_to_slot(10).unwrap();
+
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context, &user_reward_account.pubkey()).await,
+        1000
+    );
+
+    test_reward_pool
+        .sunset_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            MIN_CLAIM_PERIOD_SEC,
+        )
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += MIN_CLAIM_PERIOD_SEC as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.clone().into());
+    context.warp_to_slot(20).unwrap();
+
+    test_reward_pool
+        .remove_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &rewarder.token_account,
+        )
+        .await
+        .unwrap();
+
+    // the mining isn't refreshed in between and keeps index of the removed vault
+    test_reward_pool
+        .add_proportional_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_period,
+            clock.unix_timestamp as u64,
+            1000,
+        )
+        .await;
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100_000,
+        )
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(30).unwrap();
+
+    test_reward_pool
+        .claim(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context, &user_reward_account.pubkey()).await,
+        2000
+    );
+}
@@ -0,0 +1,167 @@
+use crate::utils::*;
+use everlend_rewards::find_vault_program_address;
+use everlend_rewards::state::{DistributionMode, RewardPool, Role};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+async fn add_vault_as(
+    context: &mut ProgramTestContext,
+    test_reward_pool: &TestRewards,
+    liquidity_mint: &Pubkey,
+    reward_mint: &Pubkey,
+    signer: &Keypair,
+) -> BanksClientResult<Pubkey> {
+    let (reward_pool, _) = test_reward_pool.get_pool_addresses(liquidity_mint);
+    let (vault, _) = find_vault_program_address(&everlend_rewards::id(), &reward_pool, reward_mint);
+    let (clock, _) = get_clock(context).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[everlend_rewards::instruction::add_vault_extended(
+            &everlend_rewards::id(),
+            &test_reward_pool.rewards_root.pubkey(),
+            &reward_pool,
+            reward_mint,
+            &vault,
+            &signer.pubkey(),
+            1,
+            1,
+            60,
+            clock.unix_timestamp as u64,
+            None,
+            0,
+            DistributionMode::FixedRatio,
+        )],
+        Some(&signer.pubkey()),
+        &[signer],
+        context.last_blockhash,
+    );
+
+    context.banks_client.process_transaction(tx).await?;
+
+    Ok(vault)
+}
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+
+    let vault_manager = Keypair::new();
+    transfer_sol(&mut context, &vault_manager.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .set_role(&mut context, Role::VaultManager, &vault_manager.pubkey())
+        .await
+        .unwrap();
+
+    add_vault_as(
+        &mut context,
+        &test_reward_pool,
+        &pool_mint.pubkey(),
+        &reward_mint.pubkey(),
+        &vault_manager,
+    )
+    .await
+    .unwrap();
+
+    // root funder fills the vault without being a funder of the pool
+    let funder = test_reward_pool
+        .create_token_holder(&mut context, &reward_mint.pubkey(), 10_000_000_000, 1000)
+        .await;
+
+    test_reward_pool
+        .set_role(&mut context, Role::Funder, &funder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &funder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1000,
+        )
+        .await
+        .unwrap();
+
+    let (reward_pool, _) = test_reward_pool.get_pool_addresses(&pool_mint.pubkey());
+    let reward_pool_account = get_account(&mut context, &reward_pool).await;
+    let reward_pool = RewardPool::unpack(reward_pool_account.data.borrow()).unwrap();
+
+    assert_eq!(reward_pool.vaults.len(), 1);
+    assert!(reward_pool.funders.is_empty());
+    assert_eq!(
+        get_token_balance(&mut context, &funder.token_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn missing_role() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+
+    let stranger = Keypair::new();
+    transfer_sol(&mut context, &stranger.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+
+    // vault manager is not set
+    assert!(add_vault_as(
+        &mut context,
+        &test_reward_pool,
+        &pool_mint.pubkey(),
+        &reward_mint.pubkey(),
+        &stranger,
+    )
+    .await
+    .is_err());
+
+    // pauser role does not grant vault management
+    test_reward_pool
+        .set_role(&mut context, Role::Pauser, &stranger.pubkey())
+        .await
+        .unwrap();
+
+    context.warp_to_slot(3).unwrap();
+
+    assert!(add_vault_as(
+        &mut context,
+        &test_reward_pool,
+        &pool_mint.pubkey(),
+        &reward_mint.pubkey(),
+        &stranger,
+    )
+    .await
+    .is_err());
+}
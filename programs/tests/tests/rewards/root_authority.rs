@@ -0,0 +1,89 @@
+use crate::utils::*;
+use everlend_rewards::find_reward_pool_program_address;
+use everlend_rewards::state::{RewardPool, RewardsRoot};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let new_authority = Keypair::new();
+    let wrong_authority = Keypair::new();
+
+    test_reward_pool
+        .propose_root_authority(&mut context, &new_authority.pubkey())
+        .await
+        .unwrap();
+
+    // only proposed authority can accept
+    test_reward_pool
+        .accept_root_authority(&mut context, &wrong_authority)
+        .await
+        .unwrap_err();
+
+    test_reward_pool
+        .accept_root_authority(&mut context, &new_authority)
+        .await
+        .unwrap();
+
+    let rewards_root = RewardsRoot::unpack(
+        get_account(&mut context, &test_reward_pool.rewards_root.pubkey())
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+
+    assert_eq!(rewards_root.authority, new_authority.pubkey());
+    assert_eq!(rewards_root.pending_authority, None);
+
+    // previous authority has no access anymore
+    let funder = Keypair::new();
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &funder.pubkey())
+        .await
+        .unwrap_err();
+
+    transfer_sol(&mut context, &new_authority.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+
+    let test_reward_pool = TestRewards {
+        rewards_root: test_reward_pool.rewards_root,
+        root_authority: new_authority,
+    };
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &funder.pubkey())
+        .await
+        .unwrap();
+
+    let (reward_pool_pubkey, _) = find_reward_pool_program_address(
+        &everlend_rewards::id(),
+        &test_reward_pool.rewards_root.pubkey(),
+        &pool_mint.pubkey(),
+    );
+    let reward_pool = RewardPool::unpack(
+        &get_account(&mut context, &reward_pool_pubkey)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+
+    assert_eq!(reward_pool.funders, vec![funder.pubkey()]);
+}
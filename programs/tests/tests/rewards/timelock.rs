@@ -0,0 +1,319 @@
+use crate::utils::*;
+use everlend_rewards::state::{
+    AdminAction, PenaltyDestination, RewardPool, RewardsRoot, Role, DEFAULT_MAX_VAULTS,
+    MIN_CLAIM_PERIOD_SEC,
+};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let timelock_sec = 86_400;
+
+    // without a timelock the action is executable right away
+    let set_timelock = AdminAction::SetTimelock { timelock_sec };
+    let pending_action = test_reward_pool
+        .propose_action(&mut context, set_timelock.clone())
+        .await
+        .unwrap();
+    test_reward_pool
+        .execute_action(&mut context, &pending_action, &set_timelock)
+        .await
+        .unwrap();
+
+    let rewards_root_account =
+        get_account(&mut context, &test_reward_pool.rewards_root.pubkey()).await;
+    let rewards_root = RewardsRoot::unpack(rewards_root_account.data.borrow()).unwrap();
+    assert_eq!(rewards_root.timelock_sec, timelock_sec);
+    assert!(context
+        .banks_client
+        .get_account(pending_action)
+        .await
+        .unwrap()
+        .is_none());
+
+    // direct changes are rejected
+    assert!(test_reward_pool
+        .update_pool(
+            &mut context,
+            &pool_mint.pubkey(),
+            3600,
+            0,
+            0,
+            PenaltyDestination::Treasury,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .is_err());
+
+    let (reward_pool, _) = test_reward_pool.get_pool_addresses(&pool_mint.pubkey());
+    let update_pool = AdminAction::UpdatePool {
+        reward_pool,
+        lock_time_sec: 3600,
+        unbonding_period_sec: 0,
+        early_exit_penalty_bps: 0,
+        penalty_destination: PenaltyDestination::Treasury,
+        max_vaults: DEFAULT_MAX_VAULTS,
+    };
+    let pending_action = test_reward_pool
+        .propose_action(&mut context, update_pool.clone())
+        .await
+        .unwrap();
+
+    assert!(test_reward_pool
+        .execute_action(&mut context, &pending_action, &update_pool)
+        .await
+        .is_err());
+
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+    clock.unix_timestamp += timelock_sec as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(3).unwrap();
+
+    test_reward_pool
+        .execute_action(&mut context, &pending_action, &update_pool)
+        .await
+        .unwrap();
+
+    let reward_pool_account = get_account(&mut context, &reward_pool).await;
+    let reward_pool = RewardPool::unpack(reward_pool_account.data.borrow()).unwrap();
+    assert_eq!(reward_pool.lock_time_sec, 3600);
+}
+
+#[tokio::test]
+async fn cancel() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &Keypair::new(), 0)
+        .await
+        .unwrap();
+
+    let set_authority = AdminAction::SetAuthority {
+        new_authority: Keypair::new().pubkey(),
+    };
+    let pending_action = test_reward_pool
+        .propose_action(&mut context, set_authority)
+        .await
+        .unwrap();
+
+    // only the root authority cancels
+    let stranger = Keypair::new();
+    assert!(test_reward_pool
+        .cancel_action(&mut context, &pending_action, &stranger)
+        .await
+        .is_err());
+
+    let authority = Keypair::from_bytes(&test_reward_pool.root_authority.to_bytes()).unwrap();
+    test_reward_pool
+        .cancel_action(&mut context, &pending_action, &authority)
+        .await
+        .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(pending_action)
+        .await
+        .unwrap()
+        .is_none());
+
+    let rewards_root_account =
+        get_account(&mut context, &test_reward_pool.rewards_root.pubkey()).await;
+    let rewards_root = RewardsRoot::unpack(rewards_root_account.data.borrow()).unwrap();
+    assert_eq!(rewards_root.pending_authority, None);
+    assert_eq!(rewards_root.next_action_id, 1);
+}
+
+#[tokio::test]
+async fn sunset_vault() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            3600,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    // users are given the minimal time to claim
+    assert!(test_reward_pool
+        .sunset_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            MIN_CLAIM_PERIOD_SEC - 1,
+        )
+        .await
+        .is_err());
+
+    let timelock_sec = 86_400;
+    let set_timelock = AdminAction::SetTimelock { timelock_sec };
+    let pending_action = test_reward_pool
+        .propose_action(&mut context, set_timelock.clone())
+        .await
+        .unwrap();
+    test_reward_pool
+        .execute_action(&mut context, &pending_action, &set_timelock)
+        .await
+        .unwrap();
+
+    // direct sunset is rejected
+    assert!(test_reward_pool
+        .sunset_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            MIN_CLAIM_PERIOD_SEC,
+        )
+        .await
+        .is_err());
+
+    let sunset_vault = AdminAction::SunsetVault {
+        reward_pool,
+        reward_mint: reward_mint.pubkey(),
+        claim_period_sec: MIN_CLAIM_PERIOD_SEC,
+    };
+    let pending_action = test_reward_pool
+        .propose_action(&mut context, sunset_vault.clone())
+        .await
+        .unwrap();
+
+    clock.unix_timestamp += timelock_sec as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(3).unwrap();
+
+    test_reward_pool
+        .execute_action(&mut context, &pending_action, &sunset_vault)
+        .await
+        .unwrap();
+
+    let reward_pool_account = get_account(&mut context, &reward_pool).await;
+    let reward_pool = RewardPool::unpack(reward_pool_account.data.borrow()).unwrap();
+    assert_eq!(reward_pool.vaults[0].ended_at, clock.unix_timestamp as u64);
+    assert_eq!(
+        reward_pool.vaults[0].claim_deadline,
+        clock.unix_timestamp as u64 + MIN_CLAIM_PERIOD_SEC
+    );
+}
+
+#[tokio::test]
+async fn root_settings() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    let (reward_pool, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let timelock_sec = 86_400;
+    let set_timelock = AdminAction::SetTimelock { timelock_sec };
+    let pending_action = test_reward_pool
+        .propose_action(&mut context, set_timelock.clone())
+        .await
+        .unwrap();
+    test_reward_pool
+        .execute_action(&mut context, &pending_action, &set_timelock)
+        .await
+        .unwrap();
+
+    let treasury = Keypair::new().pubkey();
+    let pauser = Keypair::new().pubkey();
+    let deposit_authority = Keypair::new().pubkey();
+
+    // direct changes are rejected
+    assert!(test_reward_pool
+        .set_treasury(&mut context, &treasury)
+        .await
+        .is_err());
+    assert!(test_reward_pool
+        .set_role(&mut context, Role::Pauser, &pauser)
+        .await
+        .is_err());
+    assert!(test_reward_pool
+        .set_deposit_authority(&mut context, &pool_mint.pubkey(), Some(deposit_authority))
+        .await
+        .is_err());
+
+    let actions = vec![
+        AdminAction::SetTreasury { treasury },
+        AdminAction::SetRole {
+            role: Role::Pauser,
+            account: pauser,
+        },
+        AdminAction::SetDepositAuthority {
+            reward_pool,
+            deposit_authority: Some(deposit_authority),
+        },
+    ];
+    let mut pending_actions = vec![];
+    for action in actions.iter() {
+        pending_actions.push(
+            test_reward_pool
+                .propose_action(&mut context, action.clone())
+                .await
+                .unwrap(),
+        );
+    }
+
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+    clock.unix_timestamp += timelock_sec as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(3).unwrap();
+
+    for (action, pending_action) in actions.iter().zip(pending_actions.iter()) {
+        test_reward_pool
+            .execute_action(&mut context, pending_action, action)
+            .await
+            .unwrap();
+    }
+
+    let rewards_root_account =
+        get_account(&mut context, &test_reward_pool.rewards_root.pubkey()).await;
+    let rewards_root = RewardsRoot::unpack(rewards_root_account.data.borrow()).unwrap();
+    assert_eq!(rewards_root.treasury, treasury);
+    assert_eq!(rewards_root.pauser, pauser);
+
+    let reward_pool_account = get_account(&mut context, &reward_pool).await;
+    let reward_pool = RewardPool::unpack(reward_pool_account.data.borrow()).unwrap();
+    assert_eq!(reward_pool.deposit_authority, Some(deposit_authority));
+}
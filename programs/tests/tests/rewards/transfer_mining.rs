@@ -0,0 +1,309 @@
+use crate::utils::*;
+use everlend_rewards::find_mining_program_address;
+use everlend_rewards::instruction;
+use everlend_rewards::state::{
+    DistributionMode, InitRewardPoolParams, Mining, RewardPool, RewardVault, MAX_BOOST_LOCK_SEC,
+};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::transaction::Transaction;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+async fn setup(
+    context: &mut ProgramTestContext,
+) -> (TestRewards, Keypair, Keypair, TokenHolder, Pubkey) {
+    let test_reward_pool = TestRewards::new(context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(context, &reward_mint).await.unwrap();
+    let (clock, _) = get_clock(context).await;
+
+    test_reward_pool
+        .add_vault(
+            context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            3600,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let reward_amount = 1_000_000;
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            reward_amount,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(context, &pool_mint.pubkey(), 10_000_000_000, 50_000)
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    // accrue rewards of one period
+    let (mut clock, mut clock_account) = get_clock(context).await;
+    clock.unix_timestamp += 3600;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    (
+        test_reward_pool,
+        pool_mint,
+        reward_mint,
+        token_holder,
+        mining_account,
+    )
+}
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let (test_reward_pool, pool_mint, reward_mint, token_holder, mining_account) =
+        setup(&mut context).await;
+
+    let new_owner = Keypair::new();
+    transfer_sol(&mut context, &new_owner.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+
+    let destination_mining_account = test_reward_pool
+        .transfer_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.owner,
+            &new_owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    // emptied mining is closed
+    assert!(context
+        .banks_client
+        .get_account(mining_account)
+        .await
+        .unwrap()
+        .is_none());
+
+    let destination_mining = Mining::unpack(
+        get_account(&mut context, &destination_mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(destination_mining.owner, new_owner.pubkey());
+    assert_eq!(destination_mining.amount, 50_000);
+    assert_eq!(destination_mining.indexes[0].rewards, 500);
+
+    let (reward_pool, _) = test_reward_pool.get_pool_addresses(&pool_mint.pubkey());
+    let reward_pool =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool.total_amount, 50_000);
+
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim(
+            &mut context,
+            &new_owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &user_reward_account.pubkey()).await,
+        500
+    );
+}
+
+#[tokio::test]
+async fn partial() {
+    let mut context = program_test().start_with_context().await;
+
+    let (test_reward_pool, pool_mint, _, token_holder, mining_account) = setup(&mut context).await;
+
+    let new_owner = Keypair::new();
+    let destination_mining_account = test_reward_pool
+        .transfer_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.owner,
+            &new_owner,
+            20_000,
+        )
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.amount, 30_000);
+    assert_eq!(mining.indexes[0].rewards, 300);
+
+    let destination_mining = Mining::unpack(
+        get_account(&mut context, &destination_mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(destination_mining.amount, 20_000);
+    assert_eq!(destination_mining.indexes[0].rewards, 200);
+
+    // can't transfer more than staked
+    assert!(test_reward_pool
+        .transfer_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.owner,
+            &new_owner,
+            30_001,
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn fail_without_destination_signature() {
+    let mut context = program_test().start_with_context().await;
+
+    let (test_reward_pool, pool_mint, _, token_holder, mining_account) = setup(&mut context).await;
+
+    let (reward_pool, _) = test_reward_pool.get_pool_addresses(&pool_mint.pubkey());
+    let new_owner = Keypair::new().pubkey();
+    let (destination_mining, _) =
+        find_mining_program_address(&everlend_rewards::id(), &new_owner, &reward_pool);
+
+    // stake and its locks can't be pushed onto an owner without consent
+    let mut transfer_mining = instruction::transfer_mining(
+        &everlend_rewards::id(),
+        &test_reward_pool.rewards_root.pubkey(),
+        &reward_pool,
+        &mining_account,
+        &destination_mining,
+        &token_holder.owner.pubkey(),
+        &new_owner,
+        50_000,
+    );
+    transfer_mining.accounts[5].is_signer = false;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_mining],
+        Some(&token_holder.owner.pubkey()),
+        &[&token_holder.owner],
+        context.last_blockhash,
+    );
+    assert!(context.banks_client.process_transaction(tx).await.is_err());
+}
+
+#[tokio::test]
+async fn transfer_locks() {
+    let timestamp = 1_000_000;
+
+    let mut reward_pool = RewardPool::init(InitRewardPoolParams {
+        rewards_root: Pubkey::new_unique(),
+        bump: 0,
+        liquidity_mint: Pubkey::new_unique(),
+        lock_time_sec: 0,
+    });
+
+    reward_pool
+        .add_vault(RewardVault {
+            reward_mint: Pubkey::new_unique(),
+            reward_period_sec: 60,
+            distribution_starts_at: timestamp,
+            reward_max_amount_per_period: 1_000,
+            distribution_mode: DistributionMode::Proportional,
+            index_updated_at: timestamp,
+            total_funded: u64::MAX,
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut source = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+    let mut destination = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+
+    reward_pool
+        .deposit(&mut source, 1_000, 0, timestamp)
+        .unwrap();
+    reward_pool
+        .deposit(&mut source, 3_000, MAX_BOOST_LOCK_SEC, timestamp)
+        .unwrap();
+    assert_eq!(reward_pool.total_weighted_amount, 13_000);
+
+    // unlocked amount goes first
+    reward_pool
+        .transfer(&mut source, &mut destination, 2_000, timestamp)
+        .unwrap();
+
+    assert_eq!(source.amount, 2_000);
+    assert_eq!(source.locks.len(), 1);
+    assert_eq!(source.locks[0].amount, 2_000);
+    assert_eq!(source.weighted_amount, 8_000);
+
+    assert_eq!(destination.amount, 2_000);
+    assert_eq!(destination.locks.len(), 1);
+    assert_eq!(destination.locks[0].amount, 1_000);
+    assert_eq!(destination.locks[0].lock_period_sec, MAX_BOOST_LOCK_SEC);
+    assert_eq!(destination.weighted_amount, 5_000);
+
+    assert_eq!(reward_pool.total_amount, 4_000);
+    assert_eq!(reward_pool.total_weighted_amount, 13_000);
+
+    // transferred lock is kept
+    assert_eq!(
+        destination.unlocked_amount(reward_pool.lock_time_sec, timestamp + 60),
+        1_000
+    );
+}
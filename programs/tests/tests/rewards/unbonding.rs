@@ -0,0 +1,170 @@
+use crate::utils::*;
+use everlend_rewards::state::{Mining, PenaltyDestination, RewardPool, DEFAULT_MAX_VAULTS};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let liquidity_mint = Keypair::new();
+    let unbonding_period_sec = 3600;
+    let initial_balance = 10_000;
+
+    let (reward_pool, reward_pool_spl) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .update_pool(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            0,
+            unbonding_period_sec,
+            0,
+            PenaltyDestination::Treasury,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            initial_balance,
+        )
+        .await;
+
+    let deposit_amount = 1000;
+    let request_amount = 400;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    // instant withdrawal is disabled
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+            false,
+        )
+        .await
+        .unwrap_err();
+
+    test_reward_pool
+        .request_withdraw(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.owner,
+            request_amount,
+        )
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        &get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.amount, deposit_amount - request_amount);
+    assert_eq!(mining.unbonding_amount, request_amount);
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(
+        reward_pool_account.total_amount,
+        deposit_amount - request_amount
+    );
+
+    // unbonding period is not passed yet
+    test_reward_pool
+        .finalize_withdraw(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+        )
+        .await
+        .unwrap_err();
+
+    test_reward_pool
+        .cancel_withdraw(&mut context, &liquidity_mint.pubkey(), &token_holder.owner)
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        &get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.amount, deposit_amount);
+    assert_eq!(mining.unbonding_amount, 0);
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+    assert_eq!(reward_pool_account.total_amount, deposit_amount);
+
+    test_reward_pool
+        .request_withdraw(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+    clock.unix_timestamp += unbonding_period_sec as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(3).unwrap();
+
+    test_reward_pool
+        .finalize_withdraw(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+        )
+        .await
+        .unwrap();
+
+    let token_balance = get_token_balance(&mut context, &token_holder.token_account).await;
+    assert_eq!(token_balance, initial_balance);
+
+    let pool_token_balance = get_token_balance(&mut context, &reward_pool_spl).await;
+    assert_eq!(pool_token_balance, 0);
+
+    assert!(context
+        .banks_client
+        .get_account(mining_account)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,144 @@
+use crate::utils::*;
+use everlend_rewards::state::{Mining, PenaltyDestination, DEFAULT_MAX_VAULTS};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    let lock_time_sec = 3600;
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, lock_time_sec)
+        .await
+        .unwrap();
+
+    let deposit_amount = 1_000;
+    let mut users = vec![];
+    for _ in 0..2 {
+        let token_holder = test_reward_pool
+            .create_token_holder(
+                &mut context,
+                &pool_mint.pubkey(),
+                10_000_000_000,
+                deposit_amount,
+            )
+            .await;
+
+        users.push(token_holder);
+    }
+
+    // deposit before the lock is tightened
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &users[0].token_account,
+            &users[0].owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .update_pool(
+            &mut context,
+            &pool_mint.pubkey(),
+            lock_time_sec * 2,
+            0,
+            0,
+            PenaltyDestination::Treasury,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &users[1].token_account,
+            &users[1].owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        &get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.locks[0].lock_time_sec, lock_time_sec);
+
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+    clock.unix_timestamp += lock_time_sec as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    // existing deposit keeps its lock time
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &users[0].token_account,
+            &users[0].owner,
+            deposit_amount,
+            false,
+        )
+        .await
+        .unwrap();
+
+    // new deposit is locked with the tightened lock time
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &users[1].token_account,
+            &users[1].owner,
+            deposit_amount,
+            false,
+        )
+        .await
+        .unwrap_err();
+
+    // loosened lock time applies to all deposits
+    test_reward_pool
+        .update_pool(
+            &mut context,
+            &pool_mint.pubkey(),
+            0,
+            0,
+            0,
+            PenaltyDestination::Treasury,
+            DEFAULT_MAX_VAULTS,
+        )
+        .await
+        .unwrap();
+
+    context.warp_to_slot(20).unwrap();
+
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &users[1].token_account,
+            &users[1].owner,
+            deposit_amount,
+            false,
+        )
+        .await
+        .unwrap();
+}
@@ -0,0 +1,318 @@
+use crate::utils::*;
+use everlend_rewards::state::RewardPool;
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::sysvar::clock;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use std::borrow::Borrow;
+
+use super::TestRewards;
+
+#[tokio::test]
+async fn success() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+
+    let (reward_pool_pubkey, _) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    let distribution_starts_at = clock.unix_timestamp as u64;
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            reward_period,
+            distribution_starts_at,
+        )
+        .await;
+
+    let reward_amount = 1_000_000;
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            reward_amount,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await
+        .unwrap();
+
+    let deposit_amount = 50_000;
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &pool_mint.pubkey(),
+            10_000_000_000,
+            deposit_amount,
+        )
+        .await;
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    // one period with the initial ratio
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.clone().into());
+    context.warp_to_slot(10).unwrap();
+
+    // double the rewards
+    test_reward_pool
+        .update_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            50,
+            1,
+            reward_period,
+            distribution_starts_at,
+            None,
+            0,
+        )
+        .await
+        .unwrap();
+
+    let reward_pool = RewardPool::unpack(
+        &get_account(&mut context, &reward_pool_pubkey)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(reward_pool.vaults[0].ratio_base, 50);
+    assert_eq!(
+        reward_pool.vaults[0].index_updated_at,
+        clock.unix_timestamp as u64
+    );
+
+    // one period with the updated ratio
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(20).unwrap();
+
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+
+    let user_reward = get_token_balance(&mut context, &user_reward_account.pubkey()).await;
+    assert_eq!(user_reward, 500 + 1000);
+}
+
+#[tokio::test]
+async fn successive_updates() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    let distribution_starts_at = clock.unix_timestamp as u64;
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            reward_period,
+            distribution_starts_at,
+        )
+        .await;
+
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            1_000_000,
+        )
+        .await;
+    test_reward_pool
+        .add_funder(&mut context, &pool_mint.pubkey(), &rewarder.owner.pubkey())
+        .await
+        .unwrap();
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1_000_000,
+        )
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(&mut context, &pool_mint.pubkey(), 10_000_000_000, 50_000)
+        .await;
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &pool_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            50_000,
+        )
+        .await
+        .unwrap();
+
+    // the mining isn't refreshed until the claim: two periods with 500 per period,
+    // half a period with 1000 and one period with 2500
+    for (slot, &(elapsed, ratio_base)) in [(reward_period * 2, 50), (reward_period / 2, 20)]
+        .iter()
+        .enumerate()
+    {
+        clock.unix_timestamp += elapsed as i64;
+        clock_account.data = bincode::serialize(&clock).unwrap();
+        context.set_account(&clock::id(), &clock_account.clone().into());
+        context.warp_to_slot(10 * (slot as u64 + 1)).unwrap();
+
+        test_reward_pool
+            .update_vault(
+                &mut context,
+                &pool_mint.pubkey(),
+                &reward_mint.pubkey(),
+                ratio_base,
+                1,
+                reward_period,
+                distribution_starts_at,
+                None,
+                0,
+            )
+            .await
+            .unwrap();
+    }
+
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(30).unwrap();
+
+    let user_reward_account = Keypair::new();
+    test_reward_pool
+        .claim(
+            &mut context,
+            &token_holder.owner,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            &user_reward_account,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, &user_reward_account.pubkey()).await,
+        1000 + 500 + 2500
+    );
+}
+
+#[tokio::test]
+async fn unauthorized() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+
+    let pool_mint = Keypair::new();
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &pool_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (clock, _) = get_clock(&mut context).await;
+
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            60,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let fake_authority = TestRewards {
+        rewards_root: Keypair::from_bytes(&test_reward_pool.rewards_root.to_bytes()).unwrap(),
+        root_authority: Keypair::new(),
+    };
+    transfer_sol(
+        &mut context,
+        &fake_authority.root_authority.pubkey(),
+        1_000_000_000,
+    )
+    .await
+    .unwrap();
+
+    fake_authority
+        .update_vault(
+            &mut context,
+            &pool_mint.pubkey(),
+            &reward_mint.pubkey(),
+            1,
+            1,
+            60,
+            clock.unix_timestamp as u64,
+            None,
+            0,
+        )
+        .await
+        .unwrap_err();
+}
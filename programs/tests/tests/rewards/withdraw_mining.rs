@@ -1,8 +1,13 @@
 use std::borrow::Borrow;
 
 use crate::{rewards::TestRewards, utils::*};
-use everlend_rewards::state::{Mining, RewardPool};
+use everlend_rewards::state::{
+    InitRewardPoolParams, LockTranche, Mining, RewardPool, MAX_LOCK_TRANCHES,
+};
+use everlend_utils::EverlendError;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
 use solana_program_test::*;
+use solana_sdk::sysvar::clock;
 use solana_sdk::{program_pack::Pack, signature::Keypair, signer::Signer};
 
 #[tokio::test]
@@ -70,6 +75,8 @@ async fn success() {
             &liquidity_mint.pubkey(),
             &token_holder.token_account,
             &token_holder.owner,
+            first_deposit_amount,
+            false,
         )
         .await
         .unwrap();
@@ -103,7 +110,415 @@ async fn success() {
             &liquidity_mint.pubkey(),
             &token_holder.token_account,
             &token_holder.owner,
+            first_deposit_amount,
+            false,
+        )
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn partial() {
+    let initial_balance = 100000;
+
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+    let liquidity_mint = Keypair::new();
+
+    let (reward_pool, reward_pool_spl) = test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            initial_balance,
+        )
+        .await;
+
+    let deposit_amount = 1250;
+    let withdraw_amount = 1000;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    let mining_account_info = get_account(&mut context, &mining_account).await;
+    let deposited_mining = Mining::unpack(&mining_account_info.data.borrow()).unwrap();
+
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            withdraw_amount,
+            false,
+        )
+        .await
+        .unwrap();
+
+    let mining_account_info = get_account(&mut context, &mining_account).await;
+    let mining = Mining::unpack(&mining_account_info.data.borrow()).unwrap();
+
+    assert_eq!(mining.owner, token_holder.owner.pubkey());
+    assert_eq!(mining.amount, deposit_amount - withdraw_amount);
+    assert_eq!(mining.last_deposit_time, deposited_mining.last_deposit_time);
+
+    let reward_pool_account =
+        RewardPool::unpack(get_account(&mut context, &reward_pool).await.data.borrow()).unwrap();
+
+    assert_eq!(
+        reward_pool_account.total_amount,
+        deposit_amount - withdraw_amount
+    );
+
+    let token_balance = get_token_balance(&mut context, &token_holder.token_account).await;
+    assert_eq!(
+        token_balance,
+        initial_balance - deposit_amount + withdraw_amount
+    );
+
+    let pool_token_balance = get_token_balance(&mut context, &reward_pool_spl).await;
+    assert_eq!(pool_token_balance, deposit_amount - withdraw_amount);
+
+    context.warp_to_slot(3).unwrap();
+
+    // can't withdraw more than deposited
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            withdraw_amount,
+            false,
+        )
+        .await
+        .unwrap_err();
+
+    // withdraw the rest and close the mining account
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount - withdraw_amount,
+            false,
+        )
+        .await
+        .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(mining_account)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn unclaimed_rewards() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+    let liquidity_mint = Keypair::new();
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, 0)
+        .await
+        .unwrap();
+
+    let reward_mint = Keypair::new();
+    create_mint(&mut context, &reward_mint).await.unwrap();
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+
+    let reward_period = 3600;
+    test_reward_pool
+        .add_vault(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &reward_mint.pubkey(),
+            100,
+            1,
+            reward_period,
+            clock.unix_timestamp as u64,
+        )
+        .await;
+
+    let reward_amount = 1_000_000;
+    let rewarder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &reward_mint.pubkey(),
+            10_000_000_000,
+            reward_amount,
+        )
+        .await;
+
+    test_reward_pool
+        .add_funder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &rewarder.owner.pubkey(),
+        )
+        .await
+        .unwrap();
+
+    test_reward_pool
+        .fill_vault(
+            &mut context,
+            &rewarder,
+            &liquidity_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await
+        .unwrap();
+
+    let deposit_amount = 50_000;
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            deposit_amount,
+        )
+        .await;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    // update solana clock
+    clock.unix_timestamp += reward_period as i64;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(10).unwrap();
+
+    // can't close the account with unclaimed rewards
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount,
+            false,
+        )
+        .await
+        .unwrap_err();
+
+    // partial withdraw keeps rewards
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount / 2,
+            false,
+        )
+        .await
+        .unwrap();
+
+    let mining_account_info = get_account(&mut context, &mining_account).await;
+    let mining = Mining::unpack(&mining_account_info.data.borrow()).unwrap();
+    assert_eq!(mining.indexes[0].rewards, 500);
+
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            deposit_amount / 2,
+            true,
+        )
+        .await
+        .unwrap();
+
+    let token_balance = get_token_balance(&mut context, &token_holder.token_account).await;
+    assert_eq!(token_balance, deposit_amount);
+
+    assert!(context
+        .banks_client
+        .get_account(mining_account)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn locked_tranches() {
+    let mut context = program_test().start_with_context().await;
+
+    let test_reward_pool = TestRewards::new(&mut context).await;
+    let liquidity_mint = Keypair::new();
+    let lock_time_sec = 3600;
+
+    test_reward_pool
+        .create_mint_and_initialize_pool(&mut context, &liquidity_mint, lock_time_sec)
+        .await
+        .unwrap();
+
+    let token_holder = test_reward_pool
+        .create_token_holder(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            10_000_000_000,
+            10_000,
+        )
+        .await;
+
+    let first_deposit_amount = 1000;
+    let second_deposit_amount = 500;
+
+    let mining_account = test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            first_deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    let (mut clock, mut clock_account) = get_clock(&mut context).await;
+    clock.unix_timestamp += lock_time_sec as i64 / 2;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.clone().into());
+    context.warp_to_slot(3).unwrap();
+
+    test_reward_pool
+        .deposit_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            second_deposit_amount,
+        )
+        .await
+        .unwrap();
+
+    let mining = Mining::unpack(
+        &get_account(&mut context, &mining_account)
+            .await
+            .data
+            .borrow(),
+    )
+    .unwrap();
+    assert_eq!(mining.locks.len(), 2);
+
+    clock.unix_timestamp += lock_time_sec as i64 / 2;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.clone().into());
+    context.warp_to_slot(5).unwrap();
+
+    // first tranche is unlocked
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            first_deposit_amount,
+            false,
+        )
+        .await
+        .unwrap();
+
+    // second tranche is still locked
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            second_deposit_amount,
+            false,
         )
         .await
         .unwrap_err();
+
+    clock.unix_timestamp += lock_time_sec as i64 / 2;
+    clock_account.data = bincode::serialize(&clock).unwrap();
+    context.set_account(&clock::id(), &clock_account.into());
+    context.warp_to_slot(7).unwrap();
+
+    test_reward_pool
+        .withdraw_mining(
+            &mut context,
+            &liquidity_mint.pubkey(),
+            &token_holder.token_account,
+            &token_holder.owner,
+            second_deposit_amount,
+            false,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn full_lock_tranches() {
+    let timestamp = 1_000_000;
+    let lock_time_sec = 3600;
+
+    let mut reward_pool = RewardPool::init(InitRewardPoolParams {
+        rewards_root: Pubkey::new_unique(),
+        bump: 0,
+        liquidity_mint: Pubkey::new_unique(),
+        lock_time_sec,
+    });
+    let mut mining = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+
+    for i in 0..MAX_LOCK_TRANCHES as u64 {
+        reward_pool
+            .deposit(&mut mining, 100, 0, timestamp + i)
+            .unwrap();
+    }
+    let locks = mining.locks.clone();
+    let deposited_at = |locks: &[LockTranche]| -> Vec<u64> {
+        locks.iter().map(|lock| lock.deposited_at).collect()
+    };
+
+    // existing locks are never extended by a new deposit
+    assert_eq!(
+        reward_pool
+            .deposit(&mut mining, 100, 0, timestamp + 10)
+            .unwrap_err(),
+        ProgramError::from(EverlendError::LockTranchesFull)
+    );
+    assert_eq!(deposited_at(&mining.locks), deposited_at(&locks));
+
+    // the first tranche frees its slot once unlocked
+    let timestamp = timestamp + lock_time_sec;
+    let mut mining = Mining::initialize(Pubkey::new_unique(), 0, Pubkey::new_unique());
+    mining.locks = locks.clone();
+    mining.amount = 100 * MAX_LOCK_TRANCHES as u64;
+    reward_pool.deposit(&mut mining, 100, 0, timestamp).unwrap();
+
+    let mut expected = deposited_at(&locks[1..]);
+    expected.push(timestamp);
+    assert_eq!(deposited_at(&mining.locks), expected);
 }
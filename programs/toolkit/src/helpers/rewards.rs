@@ -27,3 +27,34 @@ pub fn init_rewards_root(
 
     Ok(reward_root_keypair.pubkey())
 }
+
+pub fn rewards_root_update_authority(
+    config: &Config,
+    rewards_root: &Pubkey,
+    authority: &Keypair,
+    new_authority: &Keypair,
+) -> Result<(), ClientError> {
+    let tx = Transaction::new_with_payer(
+        &[
+            everlend_rewards::instruction::propose_root_authority(
+                &everlend_rewards::id(),
+                rewards_root,
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+            everlend_rewards::instruction::accept_root_authority(
+                &everlend_rewards::id(),
+                rewards_root,
+                &new_authority.pubkey(),
+            ),
+        ],
+        Some(&config.fee_payer.pubkey()),
+    );
+
+    config.sign_and_send_and_confirm_transaction(
+        tx,
+        vec![config.fee_payer.as_ref(), authority, new_authority],
+    )?;
+
+    Ok(())
+}
@@ -1,7 +1,12 @@
-use crate::{utils::Config, ToolkitCommand};
+use crate::{
+    utils::{get_program_accounts, Config},
+    ToolkitCommand,
+};
 use clap::{Arg, ArgMatches};
-use solana_program::instruction::Instruction;
+use everlend_rewards::state::{AccountType, Mining, RewardPool};
+use solana_program::program_pack::Pack;
 use solana_sdk::transaction::Transaction;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub struct MigrateRewardsPoolCommand;
 
@@ -25,32 +30,90 @@ impl<'a> ToolkitCommand<'a> for MigrateRewardsPoolCommand {
     fn handle(&self, config: &Config, _arg_matches: Option<&ArgMatches>) -> anyhow::Result<()> {
         let acc = config.get_initialized_accounts();
 
-        let ix: Vec<Instruction> = acc
-            .token_accounts
-            .iter()
-            .map(|(_, token)| -> Instruction {
-                let (reward_pool, _) = everlend_rewards::find_reward_pool_program_address(
-                    &everlend_rewards::id(),
-                    &acc.rewards_root,
-                    &token.mint,
-                );
+        for (_, token) in acc.token_accounts.iter() {
+            let (reward_pool, _) = everlend_rewards::find_reward_pool_program_address(
+                &everlend_rewards::id(),
+                &acc.rewards_root,
+                &token.mint,
+            );
+
+            let account = config.rpc_client.get_account(&reward_pool)?;
+            if AccountType::peek(&account.data)? != AccountType::RewardPoolV1 {
+                println!("Reward pool {} is already migrated", reward_pool);
+                continue;
+            }
+
+            let pool = RewardPool::unpack_unchecked(&account.data)?;
+            let reward_mints: Vec<_> = pool.vaults.iter().map(|v| v.reward_mint).collect();
 
-                everlend_rewards::instruction::migrate_pool(
+            // unclaimed and not yet flushed rewards of the deprecated minings stay owed
+            // to their owners. Rewards of periods ending before the migration lands
+            // are taken from the vault budget on conversion
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let mut liabilities = vec![0u64; reward_mints.len()];
+            let minings = get_program_accounts(
+                config,
+                &everlend_rewards::id(),
+                AccountType::MiningV1 as u8,
+                &reward_pool,
+            )?;
+            let minings: Vec<_> = minings
+                .into_iter()
+                .map(|(pubkey, account)| Ok((pubkey, Mining::unpack_unchecked(&account.data)?)))
+                .collect::<anyhow::Result<_>>()?;
+            for (_, mining) in minings.iter() {
+                for (i, vault) in pool.vaults.iter().enumerate() {
+                    let rewards = mining
+                        .indexes
+                        .iter()
+                        .find(|index| index.reward_mint == vault.reward_mint)
+                        .map_or(0, |index| index.rewards);
+                    let pending = vault.legacy_rewards(
+                        mining.amount,
+                        mining.rewards_calculated_at,
+                        timestamp,
+                    )?;
+
+                    liabilities[i] += rewards + pending;
+                }
+            }
+
+            let tx = Transaction::new_with_payer(
+                &[everlend_rewards::instruction::migrate_pool_checked(
                     &everlend_rewards::id(),
                     &acc.rewards_root,
                     &reward_pool,
                     &config.fee_payer.pubkey(),
                     &token.mint,
-                )
-            })
-            .collect();
+                    &reward_mints,
+                    liabilities,
+                )],
+                Some(&config.fee_payer.pubkey()),
+            );
+
+            let res = config
+                .sign_and_send_and_confirm_transaction(tx, vec![config.fee_payer.as_ref()])?;
 
-        let tx = Transaction::new_with_payer(&ix, Some(&config.fee_payer.pubkey()));
+            println!("Reward pool {} migrated: {}", reward_pool, res);
+
+            // minings are converted against the reserved liabilities
+            for (mining, _) in minings {
+                let tx = Transaction::new_with_payer(
+                    &[everlend_rewards::instruction::migrate_mining(
+                        &everlend_rewards::id(),
+                        &reward_pool,
+                        &mining,
+                        &config.fee_payer.pubkey(),
+                    )],
+                    Some(&config.fee_payer.pubkey()),
+                );
 
-        let res =
-            config.sign_and_send_and_confirm_transaction(tx, vec![config.fee_payer.as_ref()])?;
+                let res = config
+                    .sign_and_send_and_confirm_transaction(tx, vec![config.fee_payer.as_ref()])?;
 
-        println!("{}", res);
+                println!("Mining {} migrated: {}", mining, res);
+            }
+        }
 
         Ok(())
     }
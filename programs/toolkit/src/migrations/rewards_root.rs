@@ -1,6 +1,7 @@
 use crate::{utils::Config, ToolkitCommand};
 use clap::{Arg, ArgMatches};
-use everlend_rewards::state::RewardsRoot;
+use everlend_rewards::state::{AccountType, RewardsRoot};
+use solana_program::program_pack::Pack;
 use solana_sdk::transaction::Transaction;
 
 pub struct MigrateRewardsRootCommand;
@@ -25,9 +26,29 @@ impl<'a> ToolkitCommand<'a> for MigrateRewardsRootCommand {
     fn handle(&self, config: &Config, _arg_matches: Option<&ArgMatches>) -> anyhow::Result<()> {
         let acc = config.get_initialized_accounts();
 
-        let r: RewardsRoot = config.get_account_unpack(&acc.rewards_root)?;
+        let account = config.rpc_client.get_account(&acc.rewards_root)?;
+        if AccountType::peek(&account.data)? != AccountType::RewardsRootV1 {
+            println!("Rewards root is already migrated");
+            return Ok(());
+        }
+
+        let r = RewardsRoot::unpack_unchecked(&account.data)?;
         println!("Migration of rewards root: \n{:?}", &r);
 
+        let tx = Transaction::new_with_payer(
+            &[everlend_rewards::instruction::migrate_root(
+                &everlend_rewards::id(),
+                &acc.rewards_root,
+                &config.fee_payer.pubkey(),
+            )],
+            Some(&config.fee_payer.pubkey()),
+        );
+
+        let res =
+            config.sign_and_send_and_confirm_transaction(tx, vec![config.fee_payer.as_ref()])?;
+
+        println!("{}", res);
+
         Ok(())
     }
 }
@@ -1,6 +1,6 @@
 use crate::helpers::{
     collateral_pool_update_manager, general_pool_update_manager, income_pools_update_manager,
-    registry_update_manager,
+    registry_update_manager, rewards_root_update_authority,
 };
 use crate::{
     utils::{arg, arg_keypair, Config},
@@ -30,7 +30,7 @@ impl<'a> ToolkitCommand<'a> for UpdateManagerCommand {
             arg_keypair(ARG_SOURCE, true).help("Old manager keypair"),
             arg_keypair(ARG_TARGET, true).help("New manager keypair"),
             arg(ARG_PROGRAM, true).help(
-                "Program to update manager: collateral-pool|general-pool|income-pools|registry|ulp",
+                "Program to update manager: collateral-pool|general-pool|income-pools|registry|rewards|ulp",
             ),
         ]
     }
@@ -84,6 +84,18 @@ impl<'a> ToolkitCommand<'a> for UpdateManagerCommand {
                 );
                 registry_update_manager(config, &initialized_accounts.registry, &source, &target)?;
             }
+            "rewards" => {
+                println!(
+                    "Updating rewards root authority: Root {}",
+                    initialized_accounts.rewards_root
+                );
+                rewards_root_update_authority(
+                    config,
+                    &initialized_accounts.rewards_root,
+                    &source,
+                    &target,
+                )?;
+            }
             _ => {
                 return Err(anyhow::anyhow!("wrong program"));
             }
@@ -1,7 +1,12 @@
 use crate::EverlendError;
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
-    program_pack::IsInitialized, pubkey::Pubkey, rent::Rent,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    rent::Rent,
 };
 
 pub trait Uninitialized {
@@ -65,6 +70,64 @@ pub fn assert_account_key(account_info: &AccountInfo, key: &Pubkey) -> ProgramRe
     }
 }
 
+/// Multisig account able to act as an authority
+pub trait MultisigAuthority {
+    /// Keys allowed to sign for the multisig
+    fn signers(&self) -> &[Pubkey];
+    /// Number of signatures required
+    fn threshold(&self) -> u8;
+}
+
+/// Assert authority signed. A multisig authority is passed in remaining accounts
+/// followed by its co-signers, the signer itself counts if it is one of them.
+/// Signature of the multisig account itself never replaces its threshold.
+pub fn assert_authority<'a, M: MultisigAuthority + Pack + IsInitialized>(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    signer: &AccountInfo<'a>,
+    remaining_accounts: &[AccountInfo<'a>],
+) -> ProgramResult {
+    if signer.key == authority && signer.is_signer && signer.owner != program_id {
+        return Ok(());
+    }
+
+    let (multisig_info, co_signers) = match remaining_accounts.split_first() {
+        Some(accounts) => accounts,
+        None => {
+            assert_account_key(signer, authority)?;
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    };
+
+    assert_account_key(multisig_info, authority)?;
+    assert_owned_by(multisig_info, program_id)?;
+
+    let multisig = M::unpack(&multisig_info.data.borrow())?;
+    let mut signed = vec![false; multisig.signers().len()];
+    for account in std::iter::once(signer).chain(co_signers) {
+        if !account.is_signer {
+            continue;
+        }
+
+        if let Some(index) = multisig.signers().iter().position(|k| k == account.key) {
+            signed[index] = true;
+        }
+    }
+
+    let signatures = signed.iter().filter(|s| **s).count();
+    if signatures < multisig.threshold() as usize {
+        msg!(
+            "Multisig {} signed by {} of {}",
+            authority,
+            signatures,
+            multisig.threshold()
+        );
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    Ok(())
+}
+
 /// Assert rent exempt
 pub fn assert_rent_exempt(rent: &Rent, account_info: &AccountInfo) -> ProgramResult {
     if !rent.is_exempt(account_info.lamports(), account_info.data_len()) {
@@ -78,8 +141,8 @@ pub fn assert_rent_exempt(rent: &Rent, account_info: &AccountInfo) -> ProgramRes
 /// Assert a non-zero amount
 pub fn assert_non_zero_amount(amount: u64) -> ProgramResult {
     if amount == 0 {
-        return Err(EverlendError::ZeroAmount.into())
+        return Err(EverlendError::ZeroAmount.into());
     }
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,3 +1,4 @@
+use crate::EverlendError;
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
@@ -41,9 +42,12 @@ pub fn transfer<'a>(
     invoke_signed(&ix, &[from, to], signers_seeds)
 }
 
+/// Realloc program account, the payer covers rent of the grown account.
+/// Rent of the shrunk one goes to the refund receiver, without it stays on the account
 pub fn realloc_with_rent<'a, 'b>(
     acc: &'a AccountInfo<'b>,
     payer: &'a AccountInfo<'b>,
+    refund_receiver: Option<&'a AccountInfo<'b>>,
     rent: &Rent,
     new_len: usize,
 ) -> ProgramResult {
@@ -58,6 +62,17 @@ pub fn realloc_with_rent<'a, 'b>(
         )?;
     }
 
+    // Refund excess lamports
+    if let Some(refund_receiver) = refund_receiver {
+        if new_len < acc.data_len() && balance.gt(&min_balance) {
+            **refund_receiver.lamports.borrow_mut() = refund_receiver
+                .lamports()
+                .checked_add(balance - min_balance)
+                .ok_or(EverlendError::MathOverflow)?;
+            **acc.lamports.borrow_mut() = min_balance;
+        }
+    }
+
     // Realloc
     acc.realloc(new_len, false)
 }
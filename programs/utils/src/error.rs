@@ -33,6 +33,66 @@ pub enum EverlendError {
 
     #[error("Lock time is still active")]
     LockTimeStillActive,
+
+    /// Unclaimed rewards
+    #[error("Mining account has unclaimed rewards")]
+    UnclaimedRewards,
+
+    /// Unauthorized funder
+    #[error("Signer is not an approved funder")]
+    UnauthorizedFunder,
+
+    /// Reward vault ended
+    #[error("Reward vault distribution has ended")]
+    RewardVaultEnded,
+
+    /// Claim deadline
+    #[error("Claim deadline has not passed yet")]
+    ClaimDeadlineNotPassed,
+
+    /// Unbonding required
+    #[error("Withdrawal must be requested first")]
+    UnbondingRequired,
+
+    /// Unbonding still active
+    #[error("Unbonding period is still active")]
+    UnbondingStillActive,
+
+    /// Paused
+    #[error("Operations are paused")]
+    Paused,
+
+    /// Not paused
+    #[error("Operation is available only while paused")]
+    NotPaused,
+
+    /// Missing role
+    #[error("Signer doesn't have the required role")]
+    MissingRole,
+
+    /// Timelock required
+    #[error("Action must be proposed through the timelock")]
+    TimelockRequired,
+
+    /// Timelocked action not ready
+    #[error("Timelocked action is not executable yet")]
+    ActionNotReady,
+
+    /// Managed pool
+    #[error("Balances of the pool are managed by its deposit authority")]
+    ManagedPool,
+
+    /// Receipt pool
+    #[error("Operation is not supported by pools with receipt tokens")]
+    ReceiptPool,
+
+    /// Claim period too short
+    #[error("Claim period is shorter than the minimum")]
+    ClaimPeriodTooShort,
+
+    /// Lock tranches full
+    #[error("No free lock tranches left on the mining")]
+    LockTranchesFull,
 }
 
 impl PrintProgramError for EverlendError {
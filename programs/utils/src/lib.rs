@@ -7,6 +7,7 @@ pub mod math;
 pub mod pda;
 
 use std::iter::Enumerate;
+use std::slice::Iter;
 
 pub use asserts::*;
 pub use error::*;
@@ -116,6 +117,15 @@ impl AccountLoader {
         Ok(acc)
     }
 
+    /// Load the accounts left after the iterator
+    pub fn remaining<'a, 'b>(
+        accounts: &'a [AccountInfo<'b>],
+        iter: &Enumerate<Iter<'a, AccountInfo<'b>>>,
+    ) -> &'a [AccountInfo<'b>] {
+        let (remaining_len, _) = iter.size_hint();
+        &accounts[accounts.len() - remaining_len..]
+    }
+
     pub fn has_more<I: Iterator>(iter: &Enumerate<I>) -> bool {
         let (remaining_len, _) = iter.size_hint();
         remaining_len > 0